@@ -1,7 +1,1239 @@
 import test from 'ava'
+import { fileURLToPath } from 'node:url'
+import { execFile } from 'node:child_process'
+import { createHash } from 'node:crypto'
+import { mkdtemp, readFile, readdir, rm, stat } from 'node:fs/promises'
+import { createServer as createHttpServer } from 'node:http'
+import { createServer } from 'node:https'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import { promisify } from 'node:util'
 
-// import { sum } from '../index.js'
+import {
+  HyperfuelClient,
+  MockHyperfuelServer,
+  buildCallTrees,
+  computeFees,
+  decodeUtxoId,
+  renderMetrics,
+} from '../index.js'
 
-test('sum from native', (t) => {
-  t.is(1 + 2, 3)
+const heightFixtures = fileURLToPath(new URL('./fixtures/height', import.meta.url))
+const tlsFixtures = fileURLToPath(new URL('./fixtures/tls', import.meta.url))
+
+const replayClient = (fixturesDir) =>
+  HyperfuelClient.new({
+    url: 'http://localhost:1',
+    fixturesMode: 'replay',
+    fixturesDir,
+  })
+
+test('replays recorded height', async (t) => {
+  const client = replayClient(heightFixtures)
+
+  t.is(await client.getHeight(), 1234567)
+})
+
+test('fails when recorded responses run out', async (t) => {
+  const client = replayClient(heightFixtures)
+  await client.getHeight()
+
+  await t.throwsAsync(() => client.getHeight(), { message: /no recorded response left/ })
+})
+
+test('fixture errors and rejected requests are not retried', async (t) => {
+  const client = replayClient(heightFixtures)
+  await client.getHeight()
+  await t.throwsAsync(() => client.getHeightWithRetry(), { message: /no recorded response left/ })
+
+  const server = createHttpServer((req, res) => {
+    res.statusCode = 400
+    res.end('bad request')
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  t.teardown(() => server.close())
+  let requests = 0
+  server.on('request', () => requests++)
+  const rejected = HyperfuelClient.new({ url: `http://127.0.0.1:${server.address().port}` })
+  await t.throwsAsync(() => rejected.getHeightWithRetry(), { message: /400 Bad Request/ })
+  t.is(requests, 1)
+})
+
+test('fails with a diff when the request does not match the recording', async (t) => {
+  const client = replayClient(heightFixtures)
+
+  const err = await t.throwsAsync(() => client.getData({ fromBlock: 0, fieldSelection: { block: ['height'] } }))
+  t.regex(err.message, /doesn't match the recorded request/)
+  t.regex(err.message, /- {3}"method": "GET"/)
+  t.regex(err.message, /\+ {3}"method": "POST"/)
+})
+
+// serves the height from a plain http server, recording the requests it receives
+const startHeightServer = async (onRequest = () => {}) => {
+  const requests = []
+  const server = createHttpServer(async (req, res) => {
+    requests.push({ url: req.url, headers: req.headers })
+    await onRequest(req)
+    res.setHeader('content-type', 'application/json')
+    res.end('{"height":13}')
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  return { server, requests, port: server.address().port }
+}
+
+// forwards requests to the upstream server, `onRequest` returns the body to forward
+// or null if it answered the request itself
+const startProxyServer = async (upstreamUrl, onRequest) => {
+  const server = createHttpServer(async (req, res) => {
+    const chunks = []
+    for await (const chunk of req) chunks.push(chunk)
+    const body = await onRequest(req, Buffer.concat(chunks), res)
+    if (body === null) return
+    const upstreamRes = await fetch(upstreamUrl + req.url, {
+      method: req.method,
+      headers: { authorization: req.headers.authorization ?? '', 'content-type': 'application/json' },
+      body: req.method === 'POST' ? body : undefined,
+    })
+    res.statusCode = upstreamRes.status
+    res.end(Buffer.from(await upstreamRes.arrayBuffer()))
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  return { server, url: `http://127.0.0.1:${server.address().port}` }
+}
+
+const hash = (n) => '0x' + n.toString(16).padStart(64, '0')
+
+const mockData = () => {
+  const heights = [10, 11, 13]
+  return {
+    blocks: heights.map((height) => ({
+      id: hash(height),
+      daHeight: 0,
+      consensusParametersVersion: 0,
+      stateTransitionBytecodeVersion: 0,
+      transactionsCount: '0x1',
+      messageReceiptCount: '0x0',
+      transactionsRoot: hash(0),
+      messageOutboxRoot: hash(0),
+      eventInboxRoot: hash(0),
+      height,
+      prevRoot: hash(height - 1),
+      time: 1700000000 + height,
+      applicationHash: hash(0),
+    })),
+    transactions: heights.map((height) => ({
+      blockHeight: height,
+      id: hash(1000 + height),
+      txType: 0,
+      status: 1,
+      time: 1700000000 + height,
+    })),
+    receipts: heights.map((height) => ({
+      receiptIndex: 0,
+      rootContractId: hash(height === 11 ? 2 : 1),
+      txId: hash(1000 + height),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: height,
+      receiptType: 6,
+      data: '0x01',
+    })),
+  }
+}
+
+// starts a mock server that is closed after the test, along with a client for it
+const startMockServer = async (t, data = mockData(), options) => {
+  const server = await MockHyperfuelServer.start(data, options)
+  t.teardown(() => server.close())
+  return { server, client: HyperfuelClient.new({ url: server.url }) }
+}
+
+const logQuery = (fromBlock) => ({
+  fromBlock,
+  receipts: [{ rootContractId: [hash(1)] }],
+  fieldSelection: {
+    block: ['height'],
+    transaction: ['id', 'block_height'],
+    receipt: ['tx_id', 'block_height', 'root_contract_id', 'data'],
+  },
+})
+
+test('mock server applies selections and paginates', async (t) => {
+  const { client } = await startMockServer(t, mockData(), { maxBlocksPerResponse: 1 })
+
+  t.is(await client.getHeight(), 13)
+
+  const first = await client.getData(logQuery(0))
+  t.is(first.nextBlock, 11)
+  t.deepEqual(
+    first.data.receipts.map((r) => r.blockHeight),
+    [10],
+  )
+
+  const heights = []
+  let fromBlock = 0
+  while (fromBlock <= 13) {
+    const res = await client.getData(logQuery(fromBlock))
+    heights.push(...res.data.blocks.map((b) => b.height))
+    fromBlock = res.nextBlock
+  }
+  t.deepEqual(heights, [10, 13])
+})
+
+test('mock server rejects requests without the bearer token', async (t) => {
+  const server = await MockHyperfuelServer.start(mockData(), { bearerToken: 'secret-token' })
+  t.teardown(() => server.close())
+
+  const authorized = HyperfuelClient.new({ url: server.url, bearerToken: 'secret-token' })
+  t.is(await authorized.getHeight(), 13)
+
+  const unauthorized = HyperfuelClient.new({ url: server.url, bearerToken: 'wrong-token' })
+  const err = await t.throwsAsync(() => unauthorized.getHeight())
+  t.regex(err.message, /401/)
+  t.notRegex(err.message, /wrong-token/)
+})
+
+test('bearer tokens are redacted from logs and span errors', async (t) => {
+  const upstream = await MockHyperfuelServer.start(mockData(), { bearerToken: 'leaky-token' })
+  t.teardown(() => upstream.close())
+  // fails the first two queries with an error that echoes the token, forwards everything else
+  let queries = 0
+  const { server, url } = await startProxyServer(upstream.url, (req, body, res) => {
+    if (req.method === 'POST' && queries++ < 2) {
+      res.statusCode = 500
+      res.end(`invalid state for ${req.headers.authorization}`)
+      return null
+    }
+    return body
+  })
+  t.teardown(() => server.close())
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-redact-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+
+  // logs go to stderr, so the client runs in a child process
+  const script = `
+    import { HyperfuelClient } from ${JSON.stringify(fileURLToPath(new URL('../index.js', import.meta.url)))}
+    const client = HyperfuelClient.new({
+      url: ${JSON.stringify(url)},
+      bearerToken: 'leaky-token',
+      endpoints: [{ url: ${JSON.stringify(url)}, priority: 1 }],
+    })
+    const spans = []
+    client.onSpan((span) => spans.push(span))
+    await client.createParquetFolder(${JSON.stringify(logQuery(0))}, ${JSON.stringify(dir)})
+    await new Promise((resolve) => setTimeout(resolve, 100))
+    console.log(JSON.stringify(spans))
+  `
+  const { stdout, stderr } = await promisify(execFile)(process.execPath, ['--input-type=module', '-e', script], {
+    env: { ...process.env, RUST_LOG: 'debug' },
+  })
+
+  const spans = JSON.parse(stdout)
+  const failed = spans.find((span) => span.name === 'query' && span.error)
+  t.regex(failed.error, /invalid state for Bearer \[REDACTED\]/)
+  t.regex(stderr, /failing over to next endpoint/)
+  t.regex(stderr, /failed to send request to hyperfuel server/)
+  t.false(stdout.includes('leaky-token'))
+  t.false(stderr.includes('leaky-token'))
+})
+
+test('response cache is keyed by query and endpoint and evicts the least recently used', async (t) => {
+  const a = await MockHyperfuelServer.start(mockData())
+  const b = await MockHyperfuelServer.start(mockData())
+  t.teardown(() => Promise.all([a.close(), b.close()]))
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-cache-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+  // the blocks before 10 are empty, so these responses have the same size
+  const query = (fromBlock) => ({ ...logQuery(fromBlock), toBlock: 5 })
+  const cacheHit = async (client, q) => (await client.getData(q)).metrics.cacheHit
+
+  const client = HyperfuelClient.new({ url: a.url, cacheDir: dir, cacheFinalityMarginBlocks: 0 })
+  t.false(await cacheHit(client, query(0)))
+  t.true(await cacheHit(client, query(0)))
+  t.false(await cacheHit(client, query(1)))
+  t.is(a.requestCount(), 2)
+
+  // the same query sent to another instance doesn't hit the entries of the first one
+  const other = HyperfuelClient.new({ url: b.url, cacheDir: dir, cacheFinalityMarginBlocks: 0 })
+  t.false(await cacheHit(other, query(0)))
+  t.is(b.requestCount(), 1)
+
+  const entries = await readdir(dir)
+  const entrySize = (await stat(join(dir, entries[0]))).size
+  const evictDir = await mkdtemp(join(tmpdir(), 'hyperfuel-cache-'))
+  t.teardown(() => rm(evictDir, { recursive: true, force: true }))
+  const small = HyperfuelClient.new({
+    url: a.url,
+    cacheDir: evictDir,
+    cacheFinalityMarginBlocks: 0,
+    cacheMaxBytes: Math.floor(entrySize * 2.5),
+  })
+  t.false(await cacheHit(small, query(0)))
+  t.false(await cacheHit(small, query(1)))
+  t.true(await cacheHit(small, query(0)))
+  // makes room by evicting query(1), which was used least recently
+  t.false(await cacheHit(small, query(2)))
+  t.is((await readdir(evictDir)).length, 2)
+  t.true(await cacheHit(small, query(0)))
+  t.true(await cacheHit(small, query(2)))
+  t.false(await cacheHit(small, query(1)))
+})
+
+// fault injection is only compiled into builds with the fault-injection feature, see `yarn build:test`
+const faultTest = HyperfuelClient.newWithFaultInjection ? test : test.skip
+
+faultTest('injected faults fail over to the next endpoint', async (t) => {
+  const server = await MockHyperfuelServer.start(mockData())
+  t.teardown(() => server.close())
+
+  const client = HyperfuelClient.newWithFaultInjection(
+    { url: server.url, endpoints: [{ url: server.url, priority: 1 }] },
+    { schedule: [{ request: 0, fault: 'server_error' }] },
+  )
+  t.is(await client.getHeight(), 13)
+  t.is(server.requestCount(), 1)
+
+  const single = HyperfuelClient.newWithFaultInjection(
+    { url: server.url },
+    { schedule: [{ request: 0, fault: 'connection_reset' }] },
+  )
+  const err = await t.throwsAsync(() => single.getHeight())
+  t.regex(err.message, /injected fault: connection_reset/)
+  t.is(await single.getHeight(), 13)
+})
+
+test('failover skips endpoints that are down or behind the start block', async (t) => {
+  const head = await MockHyperfuelServer.start(mockData())
+  t.teardown(() => head.close())
+  const behind = await MockHyperfuelServer.start(mockData(), { archiveHeight: 11 })
+  t.teardown(() => behind.close())
+
+  const client = HyperfuelClient.new({
+    url: 'http://127.0.0.1:1',
+    endpoints: [
+      { url: behind.url, priority: 1 },
+      { url: head.url, priority: 2 },
+    ],
+  })
+  // the preferred endpoint refuses connections, so the next priority answers
+  t.is(await client.getHeight(), 11)
+  t.is(behind.requestCount(), 1)
+
+  const res = await client.getData(logQuery(13))
+  t.deepEqual(
+    res.data.receipts.map((r) => r.blockHeight),
+    [13],
+  )
+  t.is(behind.requestCount(), 1)
+  t.is(head.requestCount(), 1)
+})
+
+test('requests fail when every endpoint is behind the start block', async (t) => {
+  const behind = await MockHyperfuelServer.start(mockData(), { archiveHeight: 11 })
+  t.teardown(() => behind.close())
+  const client = HyperfuelClient.new({ url: behind.url })
+
+  t.is(await client.getHeight(), 11)
+  const err = await t.throwsAsync(() => client.getData(logQuery(20)))
+  t.regex(err.message, /every endpoint is behind block 20, the highest archive height is 11/)
+  // the height is asked for again before giving up, the query itself is never sent
+  t.is(behind.requestCount(), 2)
+})
+
+test('request metrics report connection setup separately', async (t) => {
+  const server = await MockHyperfuelServer.start(mockData())
+  t.teardown(() => server.close())
+  // a host name instead of an ip address, so the connection goes through dns
+  const client = HyperfuelClient.new({ url: server.url.replace('127.0.0.1', 'localhost') })
+
+  const first = (await client.getData(logQuery(0))).metrics
+  t.true(first.newConnection)
+  t.is(typeof first.dnsTimeMs, 'number')
+  // plain http has no handshake that marks the end of the tcp connect
+  t.is(first.connectTimeMs, undefined)
+  t.is(first.tlsHandshakeTimeMs, undefined)
+  t.true(first.conversionTimeMs >= 0)
+
+  const second = (await client.getData(logQuery(0))).metrics
+  t.false(second.newConnection)
+  t.is(second.dnsTimeMs, undefined)
+
+  const totals = client.metrics()
+  t.is(totals.requests, 2)
+  t.is(totals.newConnections, 1)
+  t.is(totals.connectTimeMs, 0)
+  // both responses were converted into javascript values
+  t.true(totals.napiConversionTimeMs > 0)
+  t.is(totals.tlsHandshakeTimeMs, 0)
+})
+
+test('request metrics report the tls handshake of https connections', async (t) => {
+  const server = createServer(
+    {
+      key: await readFile(join(tlsFixtures, 'server.key')),
+      cert: await readFile(join(tlsFixtures, 'server.pem')),
+    },
+    (_, res) => {
+      res.setHeader('content-type', 'application/json')
+      res.end('{"height":13}')
+    },
+  )
+  await new Promise((resolve) => server.listen(0, 'localhost', resolve))
+  t.teardown(() => server.close())
+
+  const client = HyperfuelClient.new({
+    url: `https://localhost:${server.address().port}`,
+    tls: { caBundlePath: join(tlsFixtures, 'ca.pem') },
+  })
+  t.is(await client.getHeight(), 13)
+  t.is(await client.getHeight(), 13)
+
+  const totals = client.metrics()
+  t.is(totals.newConnections, 1)
+  t.true(totals.connectTimeMs > 0)
+  t.true(totals.tlsHandshakeTimeMs > 0)
+  t.true(totals.timeToFirstByteMs >= totals.dnsTimeMs + totals.connectTimeMs + totals.tlsHandshakeTimeMs)
+})
+
+test('renderMetrics exports the statistics of labeled clients', async (t) => {
+  const server = await MockHyperfuelServer.start(mockData(), { bearerToken: 'secret-token' })
+  t.teardown(() => server.close())
+  const label = 'render-metrics-test'
+  const client = HyperfuelClient.new({ url: server.url, bearerToken: 'secret-token', metricsLabel: label })
+  const unauthorized = HyperfuelClient.new({ url: server.url, bearerToken: 'wrong-token', metricsLabel: label })
+
+  t.is(await client.getHeight(), 13)
+  const res = await client.getData(logQuery(0))
+  await t.throwsAsync(() => unauthorized.getHeight())
+
+  const text = renderMetrics()
+  const line = (name, labels, value) =>
+    t.true(text.split('\n').includes(`${name}{client="${label}"${labels}} ${value}`), `${name}${labels} ${value}`)
+  line('hyperfuel_client_requests_total', '', 3)
+  line('hyperfuel_client_request_errors_total', ',code="401"', 1)
+  line('hyperfuel_client_response_bytes_total', '', client.metrics().responseBytes)
+  line('hyperfuel_client_request_duration_seconds_bucket', ',le="+Inf"', 2)
+  line('hyperfuel_client_request_duration_seconds_count', '', 2)
+  line('hyperfuel_client_archive_height', '', 13)
+  line('hyperfuel_client_next_block', '', res.nextBlock)
+  t.true(text.endsWith('# EOF\n'))
+})
+
+test('renderMetrics counts failovers and rate limit retries', async (t) => {
+  const upstream = await MockHyperfuelServer.start(mockData())
+  t.teardown(() => upstream.close())
+  const failing = await startProxyServer(upstream.url, (req, body, res) => {
+    res.statusCode = 500
+    res.end('down')
+    return null
+  })
+  t.teardown(() => failing.server.close())
+  let rateLimited = 0
+  const limiting = await startProxyServer(upstream.url, (req, body, res) => {
+    if (req.method === 'POST' && rateLimited++ === 0) {
+      res.statusCode = 429
+      res.setHeader('retry-after', '0')
+      res.end('slow down')
+      return null
+    }
+    return body
+  })
+  t.teardown(() => limiting.server.close())
+
+  const label = 'retry-metrics-test'
+  const failover = HyperfuelClient.new({
+    url: failing.url,
+    endpoints: [{ url: upstream.url, priority: 1 }],
+    metricsLabel: label,
+  })
+  await failover.getData(logQuery(0))
+  const limited = HyperfuelClient.new({ url: limiting.url, metricsLabel: label })
+  await limited.getData(logQuery(0))
+
+  const lines = renderMetrics().split('\n')
+  const line = (name, labels, value) =>
+    t.true(lines.includes(`${name}{client="${label}"${labels}} ${value}`), `${name}${labels} ${value}`)
+  line('hyperfuel_client_requests_total', '', 2)
+  line('hyperfuel_client_retries_total', '', 2)
+  line('hyperfuel_client_request_errors_total', ',code="500"', 1)
+  line('hyperfuel_client_request_errors_total', ',code="429"', 1)
+})
+
+test('config adds headers and a user agent suffix and routes through the proxy', async (t) => {
+  const { server, requests, port } = await startHeightServer()
+  t.teardown(() => server.close())
+
+  const client = HyperfuelClient.new({
+    url: 'http://hyperfuel.invalid',
+    httpHeaders: { 'x-gateway-key': 'gateway-secret' },
+    userAgentSuffix: 'indexer/1.0',
+    proxy: { http: `http://127.0.0.1:${port}` },
+  })
+  t.is(await client.getHeight(), 13)
+  // requests to a proxy carry the absolute url of the target
+  t.is(requests[0].url, 'http://hyperfuel.invalid/height')
+  t.is(requests[0].headers['x-gateway-key'], 'gateway-secret')
+  t.regex(requests[0].headers['user-agent'], /^hyperfuel-client-node\/\S+ indexer\/1\.0$/)
+
+  const bypass = HyperfuelClient.new({
+    url: `http://localhost:${port}`,
+    proxy: { all: 'http://127.0.0.1:1', noProxy: ['localhost'] },
+  })
+  t.is(await bypass.getHeight(), 13)
+  t.is(requests[1].url, '/height')
+})
+
+test('tls config presents the client certificate for mtls', async (t) => {
+  const server = createServer(
+    {
+      key: await readFile(join(tlsFixtures, 'server.key')),
+      cert: await readFile(join(tlsFixtures, 'server.pem')),
+      ca: await readFile(join(tlsFixtures, 'ca.pem')),
+      requestCert: true,
+      rejectUnauthorized: true,
+    },
+    (req, res) => {
+      res.setHeader('content-type', 'application/json')
+      res.end(JSON.stringify({ height: 13, subject: req.socket.getPeerCertificate().subject.CN }))
+    },
+  )
+  await new Promise((resolve) => server.listen(0, 'localhost', resolve))
+  t.teardown(() => server.close())
+  const url = `https://localhost:${server.address().port}`
+  const caBundlePath = join(tlsFixtures, 'ca.pem')
+
+  const client = HyperfuelClient.new({
+    url,
+    tls: {
+      caBundlePath,
+      clientCertPath: join(tlsFixtures, 'client.pem'),
+      clientKeyPath: join(tlsFixtures, 'client.key'),
+    },
+  })
+  t.is(await client.getHeight(), 13)
+
+  const anonymous = HyperfuelClient.new({ url, tls: { caBundlePath }, maxNumRetries: 0 })
+  await t.throwsAsync(() => anonymous.getHeight())
+
+  t.throws(() => HyperfuelClient.new({ url, tls: { clientCertPath: join(tlsFixtures, 'client.pem') } }), {
+    message: /client_cert_path and client_key_path have to be set together/,
+  })
+})
+
+test('rate limits and concurrency caps are shared within a group', async (t) => {
+  let inFlight = 0
+  let maxInFlight = 0
+  const { server } = await startHeightServer(async () => {
+    inFlight++
+    maxInFlight = Math.max(maxInFlight, inFlight)
+    await new Promise((resolve) => setTimeout(resolve, 30))
+    inFlight--
+  })
+  t.teardown(() => server.close())
+  const url = `http://127.0.0.1:${server.address().port}`
+
+  const capped = { url, maxConcurrentRequests: 2, rateLimitGroup: 'concurrency-test' }
+  const clients = [HyperfuelClient.new(capped), HyperfuelClient.new(capped)]
+  await Promise.all(clients.flatMap((client) => [0, 1, 2].map(() => client.getHeight())))
+  t.is(maxInFlight, 2)
+
+  const limited = HyperfuelClient.new({ url, maxRequestsPerSecond: 20, requestBurst: 1 })
+  const start = Date.now()
+  for (let i = 0; i < 4; i++) {
+    await limited.getHeight()
+  }
+  // the first request uses the burst, the other three wait for a slot every 50ms
+  t.true(Date.now() - start >= 140)
+  t.true(limited.metrics().rateLimitWaitMs > 0)
+})
+
+test('rate limited requests fail with a code once the retries run out', async (t) => {
+  let requests = 0
+  const server = createHttpServer((_, res) => {
+    requests++
+    res.writeHead(429, { 'retry-after': '0' })
+    res.end('slow down')
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  t.teardown(() => server.close())
+  const client = HyperfuelClient.new({ url: `http://127.0.0.1:${server.address().port}`, rateLimitRetries: 2 })
+
+  // retrying operations give up instead of waiting forever
+  const err = await t.throwsAsync(() => client.getHeightWithRetry())
+  t.is(err.code, 'RateLimited')
+  t.is(err.status, 429)
+  t.is(err.retryAfterMs, 0)
+  t.regex(err.message, /^rate limited with status code 429/)
+  t.is(requests, 3)
+
+  const [result] = await client.getDataBatch([logQuery(0)])
+  t.deepEqual(result.rateLimited, { status: 429, retryAfterMs: 0 })
+  t.regex(result.error, /rate limited with status code 429/)
+})
+
+test('getDataBatch returns results in order with per query errors', async (t) => {
+  const { client } = await startMockServer(t)
+
+  const results = await client.getDataBatch([logQuery(11), logQuery(-1), logQuery(0)], { concurrency: 2 })
+  t.is(results.length, 3)
+  t.deepEqual(
+    results[0].response.data.receipts.map((r) => r.blockHeight),
+    [13],
+  )
+  t.is(results[1].response, undefined)
+  t.regex(results[1].error, /parse query/)
+  t.deepEqual(
+    results[2].response.data.receipts.map((r) => r.blockHeight),
+    [10, 13],
+  )
+})
+
+// reads the column names and row count from the footer of a parquet file, which is encoded
+// with the thrift compact protocol
+const readParquetFooter = async (path) => {
+  const buf = await readFile(path)
+  let pos = buf.length - 8 - buf.readUInt32LE(buf.length - 8)
+
+  const varint = () => {
+    let result = 0n
+    for (let shift = 0n; ; shift += 7n) {
+      const byte = buf[pos++]
+      result |= BigInt(byte & 0x7f) << shift
+      if (!(byte & 0x80)) return result
+    }
+  }
+  const zigzag = () => {
+    const n = varint()
+    return Number((n >> 1n) ^ -(n & 1n))
+  }
+  const value = (type) => {
+    switch (type) {
+      case 1:
+      case 2:
+        return type === 1
+      case 3:
+        return buf.readInt8(pos++)
+      case 4:
+      case 5:
+      case 6:
+        return zigzag()
+      case 7:
+        pos += 8
+        return undefined
+      case 8: {
+        const len = Number(varint())
+        pos += len
+        return buf.toString('utf8', pos - len, pos)
+      }
+      case 9:
+      case 10: {
+        const header = buf[pos++]
+        const size = header >> 4 === 15 ? Number(varint()) : header >> 4
+        const elem = header & 0x0f
+        // booleans inside of lists take up a whole byte
+        return Array.from({ length: size }, () => (elem === 1 || elem === 2 ? buf[pos++] === 1 : value(elem)))
+      }
+      case 11: {
+        const size = Number(varint())
+        const types = size > 0 ? buf[pos++] : 0
+        for (let i = 0; i < size; i++) {
+          value(types >> 4)
+          value(types & 0x0f)
+        }
+        return undefined
+      }
+      case 12: {
+        const fields = {}
+        for (let id = 0; ; ) {
+          const header = buf[pos++]
+          if (header === 0) return fields
+          id = header >> 4 ? id + (header >> 4) : zigzag()
+          fields[id] = value(header & 0x0f)
+        }
+      }
+    }
+  }
+
+  // FileMetaData: 2 is the schema with the root element first, 3 is the number of rows
+  const meta = value(12)
+  return { columns: meta[2].slice(1).map((element) => element[4]), rows: meta[3] }
+}
+
+test('createParquetFolder writes the selected columns of every page', async (t) => {
+  const { client } = await startMockServer(t, mockData(), { maxBlocksPerResponse: 1 })
+
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-parquet-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+
+  await client.createParquetFolder({ ...logQuery(0), toBlock: 14 }, dir)
+
+  t.deepEqual(await readParquetFooter(join(dir, 'block.parquet')), { columns: ['height'], rows: 2 })
+  t.deepEqual(await readParquetFooter(join(dir, 'transaction.parquet')), {
+    columns: ['block_height', 'id'],
+    rows: 2,
+  })
+  t.deepEqual(await readParquetFooter(join(dir, 'receipt.parquet')), {
+    columns: ['root_contract_id', 'tx_id', 'block_height', 'data'],
+    rows: 2,
+  })
+  t.deepEqual(await readParquetFooter(join(dir, 'input.parquet')), { columns: [], rows: 0 })
+
+  // without a to_block the export includes the head block, like the paginated methods
+  const head = await mkdtemp(join(tmpdir(), 'hyperfuel-parquet-'))
+  t.teardown(() => rm(head, { recursive: true, force: true }))
+  await client.createParquetFolder(logQuery(13), head)
+  t.deepEqual(await readParquetFooter(join(head, 'block.parquet')), { columns: ['height'], rows: 1 })
+})
+
+test('pagination stops at empty ranges and fails when the server makes no progress', async (t) => {
+  const upstream = await MockHyperfuelServer.start(mockData(), { maxBlocksPerResponse: 1 })
+  t.teardown(() => upstream.close())
+  const client = HyperfuelClient.new({ url: upstream.url })
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-parquet-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+
+  // only the height is requested
+  await client.createParquetFolder({ ...logQuery(12), toBlock: 12 }, dir)
+  t.is(upstream.requestCount(), 1)
+
+  // the upstream server answers a query that ends where it starts with next_block == from_block
+  const { server, url } = await startProxyServer(upstream.url, (req, body) => {
+    if (req.method !== 'POST') return body
+    const query = JSON.parse(body)
+    return JSON.stringify({ ...query, to_block: query.from_block })
+  })
+  t.teardown(() => server.close())
+  const stalled = HyperfuelClient.new({ url })
+  const noProgress = { message: /server returned next_block 0 for a request from block 0/ }
+  await t.throwsAsync(() => stalled.createParquetFolder({ ...logQuery(0), toBlock: 14 }, dir), noProgress)
+  await t.throwsAsync(
+    () => stalled.createParquetFolder({ ...logQuery(0), toBlock: 14 }, dir, null, { concurrency: 2 }),
+    noProgress,
+  )
+})
+
+test('page sizing counts transactions in the selected tables', async (t) => {
+  const upstream = await MockHyperfuelServer.start(mockData(), { maxBlocksPerResponse: 1 })
+  t.teardown(() => upstream.close())
+  const queries = []
+  const { server, url } = await startProxyServer(upstream.url, (req, body) => {
+    if (req.method === 'POST') queries.push(JSON.parse(body))
+    return body
+  })
+  t.teardown(() => server.close())
+  const client = HyperfuelClient.new({ url, targetPageRows: 10 })
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-parquet-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+
+  // only receipts are selected, the transactions are counted by their tx_id
+  const query = {
+    fromBlock: 0,
+    toBlock: 14,
+    receipts: [{ rootContractId: [hash(1)] }],
+    fieldSelection: { receipt: ['tx_id', 'block_height'] },
+  }
+  await client.createParquetFolder(query, dir)
+
+  t.is(queries[0].max_num_transactions, null)
+  // the first page had one transaction per row, so a page of 10 rows has 10 transactions
+  t.is(queries[1].max_num_transactions, 10)
+})
+
+test('createParquetFolder fetches sub-ranges in parallel', async (t) => {
+  const { client } = await startMockServer(t, mockData(), { maxBlocksPerResponse: 1 })
+
+  const dir = await mkdtemp(join(tmpdir(), 'hyperfuel-parquet-'))
+  t.teardown(() => rm(dir, { recursive: true, force: true }))
+
+  await client.createParquetFolder({ ...logQuery(0), toBlock: 14 }, dir, null, { concurrency: 2 })
+
+  const files = (await readdir(dir)).sort()
+  t.deepEqual(files, ['block.parquet', 'input.parquet', 'output.parquet', 'receipt.parquet', 'transaction.parquet'])
+})
+
+test('getTransactions nests receipts and blocks under their transaction', async (t) => {
+  const { client } = await startMockServer(t)
+
+  const res = await client.getTransactions(logQuery(0))
+  t.deepEqual(
+    res.data.map((tx) => [tx.transaction.id, tx.block.height, tx.receipts.map((r) => r.txId)]),
+    [
+      [hash(1010), 10, [hash(1010)]],
+      [hash(1013), 13, [hash(1013)]],
+    ],
+  )
+})
+
+test('buildCallTrees nests calls and unwinds on revert', (t) => {
+  const receipt = (receiptIndex, receiptType, fields = {}) => ({
+    receiptIndex,
+    txId: hash(1),
+    txStatus: 2,
+    txType: 0,
+    blockHeight: 10,
+    receiptType,
+    ...fields,
+  })
+  const [tree] = buildCallTrees([
+    receipt(4, 9),
+    receipt(0, 0, { contractId: hash(0), to: hash(0xa) }),
+    receipt(1, 5, { contractId: hash(0xa) }),
+    receipt(2, 0, { contractId: hash(0xa), to: hash(0xb) }),
+    receipt(3, 4, { contractId: hash(0xb) }),
+  ])
+
+  t.is(tree.root.calls.length, 1)
+  const outer = tree.root.calls[0]
+  t.is(outer.contractId, hash(0xa))
+  // the script made the call, its receipt has the zero contract id
+  t.is(outer.caller, undefined)
+  t.is(outer.outcome, 'unfinished')
+  t.deepEqual(
+    outer.logs.map((r) => r.receiptIndex),
+    [1],
+  )
+  const inner = outer.calls[0]
+  t.is(inner.contractId, hash(0xb))
+  t.is(inner.caller, hash(0xa))
+  t.is(inner.outcome, 'revert')
+  t.is(inner.failure.receiptIndex, 3)
+  t.is(tree.scriptResult.receiptIndex, 4)
+})
+
+test('presetQueryGetTransfers normalizes transfer receipts', async (t) => {
+  const data = mockData()
+  data.receipts = data.receipts.map((r) => ({
+    ...r,
+    receiptType: 8,
+    contractId: r.rootContractId,
+    toAddress: hash(0xbeef),
+    assetId: hash(r.blockHeight === 13 ? 0xa2 : 0xa1),
+    amount: BigInt(r.blockHeight),
+    txStatus: r.blockHeight === 11 ? 2 : 1,
+  }))
+  const { server, client } = await startMockServer(t, data)
+
+  const res = await client.presetQueryGetTransfers(null, [hash(0xbeef)], null, 0, 14)
+  t.deepEqual(
+    res.data.map((tr) => [tr.kind, tr.from, tr.to, tr.amount, tr.blockHeight]),
+    [
+      ['transfer_out', hash(1), hash(0xbeef), 10n, 10],
+      ['transfer_out', hash(1), hash(0xbeef), 13n, 13],
+    ],
+  )
+
+  const filtered = await client.presetQueryGetTransfers([hash(0xa2)], null, null, 0, 14)
+  t.deepEqual(
+    filtered.data.map((tr) => tr.blockHeight),
+    [13],
+  )
+
+  // receipts of contracts are only selected by the contract that sent them
+  const queries = []
+  const proxy = await startProxyServer(server.url, (req, body) => {
+    if (req.method === 'POST') queries.push(JSON.parse(body))
+    return body
+  })
+  t.teardown(() => proxy.server.close())
+  const byContract = await HyperfuelClient.new({ url: proxy.url }).presetQueryGetTransfers(
+    null,
+    null,
+    [hash(1)],
+    0,
+    14,
+  )
+  t.deepEqual(
+    byContract.data.map((tr) => tr.blockHeight),
+    [10, 13],
+  )
+  t.deepEqual(
+    queries[0].receipts.map((selection) => selection.contract_id),
+    [[hash(1)]],
+  )
+})
+
+test('presetQueryGetMintsBurns derives the asset id', async (t) => {
+  const data = mockData()
+  data.receipts = data.receipts.map((r) => ({
+    ...r,
+    receiptType: r.blockHeight === 13 ? 12 : 11,
+    contractId: r.rootContractId,
+    subId: hash(7),
+    val: BigInt(r.blockHeight * 100),
+  }))
+  // a receipt without an amount is left out instead of being reported as 0
+  data.receipts.push({ ...data.receipts[0], receiptIndex: 1, val: undefined })
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.presetQueryGetMintsBurns([hash(1)], 0, 14)
+  const assetId =
+    '0x' +
+    createHash('sha256')
+      .update(Buffer.from(hash(1).slice(2) + hash(7).slice(2), 'hex'))
+      .digest('hex')
+  t.deepEqual(
+    res.data.map((e) => [e.kind, e.assetId, e.amount, e.blockHeight]),
+    [
+      ['mint', assetId, 1000n, 10],
+      ['burn', assetId, 1300n, 13],
+    ],
+  )
+})
+
+test('presetQueryGetContractDeployments pairs outputs with their transaction', async (t) => {
+  const data = mockData()
+  data.receipts = []
+  data.transactions = data.transactions.map((tx) => ({
+    ...tx,
+    txType: 1,
+    salt: hash(0x5a),
+    bytecodeRoot: hash(0xb0),
+    // only the first witness is returned by the server
+    bytecodeWitnessIndex: tx.blockHeight === 13 ? 1 : 0,
+    witnesses: '0xdeadbeef',
+  }))
+  data.outputs = data.transactions.map((tx) => ({
+    txId: tx.id,
+    txStatus: 1,
+    txType: 1,
+    blockHeight: tx.blockHeight,
+    outputType: 4,
+    contract: hash(0xc000 + tx.blockHeight),
+    stateRoot: hash(0x51),
+  }))
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.presetQueryGetContractDeployments(0, 14, {
+    contractIds: [hash(0xc00b), hash(0xc00d)],
+    includeBytecode: true,
+  })
+  t.deepEqual(res.data, [
+    {
+      contractId: hash(0xc00b),
+      stateRoot: hash(0x51),
+      bytecodeRoot: hash(0xb0),
+      salt: hash(0x5a),
+      txId: hash(1011),
+      blockHeight: 11,
+      time: 1700000011,
+      bytecode: '0xdeadbeef',
+      bytecodeUnavailable: false,
+    },
+    {
+      contractId: hash(0xc00d),
+      stateRoot: hash(0x51),
+      bytecodeRoot: hash(0xb0),
+      salt: hash(0x5a),
+      txId: hash(1013),
+      blockHeight: 13,
+      time: 1700000013,
+      bytecodeUnavailable: true,
+    },
+  ])
+})
+
+test('presetQueryGetBridgeMessages returns deposits and withdrawals by nonce', async (t) => {
+  const data = mockData()
+  data.receipts = [
+    {
+      receiptIndex: 0,
+      txId: hash(1010),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 10,
+      receiptType: 10,
+      sender: hash(0xaa),
+      recipient: hash(0xbb),
+      nonce: hash(0x4e1),
+      amount: 5n,
+      digest: hash(0xd1),
+    },
+  ]
+  data.inputs = [
+    {
+      txId: hash(1013),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 13,
+      inputType: 2,
+      sender: hash(0xbb),
+      recipient: hash(0xaa),
+      nonce: hash(0x4e2),
+      amount: 7n,
+    },
+  ]
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.presetQueryGetBridgeMessages(null, null, 0, 14)
+  t.deepEqual(
+    res.data.map((m) => [m.direction, m.nonce, m.amount, m.blockHeight]),
+    [
+      ['withdrawal', hash(0x4e1), 5n, 10],
+      ['deposit', hash(0x4e2), 7n, 13],
+    ],
+  )
+})
+
+test('decodeUtxoId splits the transaction id and output index', (t) => {
+  t.deepEqual(decodeUtxoId(hash(7) + '0003'), { txId: hash(7), outputIndex: 3 })
+  t.deepEqual(decodeUtxoId(hash(7)), { txId: hash(7) })
+  t.throws(() => decodeUtxoId('0x1234'))
+})
+
+test('getUtxoLifecycles pairs coins with the inputs that spend them', async (t) => {
+  const data = mockData()
+  const coin = (txId, blockHeight, outputType, to, amount) => ({
+    txId,
+    txStatus: 1,
+    txType: 0,
+    blockHeight,
+    outputType,
+    to,
+    assetId: hash(0xa5),
+    amount,
+  })
+  data.outputs = [
+    coin(hash(1010), 10, 0, hash(0xaa), 5n),
+    coin(hash(1010), 10, 2, hash(0xaa), 9n),
+    coin(hash(1013), 13, 0, hash(0xbb), 5n),
+  ]
+  data.inputs = [
+    {
+      txId: hash(1013),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 13,
+      inputType: 0,
+      utxoId: hash(1010),
+      owner: hash(0xaa),
+      assetId: hash(0xa5),
+      amount: 5n,
+    },
+  ]
+  const { client } = await startMockServer(t, data, { maxBlocksPerResponse: 1 })
+
+  const res = await client.getUtxoLifecycles([hash(0xaa)], null, 0, 14)
+  t.deepEqual(
+    res.data.map((c) => [c.txId, c.outputIndex, c.amount, c.spentTxId]),
+    [
+      [hash(1010), 0, 5n, hash(1013)],
+      [hash(1010), 1, 9n, undefined],
+    ],
+  )
+
+  // pages fetched in parallel are still processed in block order
+  const parallel = await client.getUtxoLifecycles([hash(0xaa)], null, 0, null, { concurrency: 2, initialChunkBlocks: 1 })
+  t.deepEqual(parallel.data, res.data)
+  t.is(parallel.nextBlock, 14)
+})
+
+test('computeBalances returns running balances and snapshots', async (t) => {
+  const data = mockData()
+  const coin = (txId, blockHeight, to, amount) => ({
+    txId,
+    txStatus: 1,
+    txType: 0,
+    blockHeight,
+    outputType: 0,
+    to,
+    assetId: hash(0xa5),
+    amount,
+  })
+  data.outputs = [coin(hash(1010), 10, hash(0xaa), 5n), coin(hash(1010), 10, hash(0xaa), 9n)]
+  data.inputs = [
+    {
+      txId: hash(1013),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 13,
+      inputType: 0,
+      owner: hash(0xaa),
+      assetId: hash(0xa5),
+      amount: 5n,
+    },
+  ]
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.computeBalances([hash(0xaa)], null, null, 10, 14, {
+    snapshotInterval: 2,
+  })
+  t.is(res.data.length, 1)
+  const [history] = res.data
+  t.deepEqual(
+    history.changes.map((c) => [c.blockHeight, c.delta, c.balance]),
+    [
+      [10, 14n, 14n],
+      [13, -5n, 9n],
+    ],
+  )
+  t.deepEqual(
+    history.snapshots.map((s) => [s.blockHeight, s.balance]),
+    [
+      [10, 14n],
+      [12, 14n],
+    ],
+  )
+})
+
+test('computeBalances tracks contracts by their receipts only', async (t) => {
+  const data = mockData()
+  data.receipts = [
+    {
+      txId: hash(1011),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 11,
+      receiptIndex: 0,
+      receiptType: 7,
+      contractId: hash(0xcc),
+      to: hash(0xcd),
+      assetId: hash(0xa5),
+      amount: 3n,
+    },
+  ]
+  data.outputs = [
+    {
+      txId: hash(1011),
+      txStatus: 1,
+      txType: 0,
+      blockHeight: 11,
+      outputType: 0,
+      to: hash(0xcc),
+      assetId: hash(0xa5),
+      amount: 4n,
+    },
+  ]
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.computeBalances(null, [hash(0xcc), hash(0xcd)], null, 10, 14)
+  t.deepEqual(
+    res.data.map((h) => [h.address, h.changes.map((c) => [c.blockHeight, c.delta])]),
+    [
+      [hash(0xcc), [[11, -3n]]],
+      [hash(0xcd), [[11, 3n]]],
+    ],
+  )
+
+  await t.throwsAsync(client.computeBalances(null, null, null, 10, 14), {
+    message: /at least one address or contract is required/,
+  })
+})
+
+test('getPredicateActivity groups spends by bytecode', async (t) => {
+  const data = mockData()
+  const input = (blockHeight, owner, amount, predicate) => ({
+    txId: hash(1000 + blockHeight),
+    txStatus: 1,
+    txType: 0,
+    blockHeight,
+    inputType: 0,
+    owner,
+    assetId: hash(0xa5),
+    amount,
+    ...(predicate && { predicate, predicateData: '0x01', predicateGasUsed: 100 }),
+  })
+  data.inputs = [
+    input(10, hash(0xbb), 1n),
+    input(10, hash(0xaa), 5n, '0x1a2b'),
+    input(13, hash(0xaa), 7n, '0x1a2b'),
+    input(13, hash(0xaa), 2n),
+  ]
+  const { client } = await startMockServer(t, data)
+
+  const res = await client.getPredicateActivity([hash(0xaa)], 0, 14)
+  t.deepEqual(
+    res.spends.map((s) => [s.blockHeight, s.inputIndex, s.amount]),
+    [
+      [10, 1, 5n],
+      [13, 0, 7n],
+    ],
+  )
+  const bytecodeHash = '0x' + createHash('sha256').update(Buffer.from('1a2b', 'hex')).digest('hex')
+  t.deepEqual(res.stats, [
+    {
+      bytecodeHash,
+      predicateRoots: [hash(0xaa)],
+      spendCount: 2,
+      failedSpendCount: 0,
+      totalGasUsed: 200,
+      assetsMoved: [{ assetId: hash(0xa5), amount: 12n }],
+      firstBlockHeight: 10,
+      lastBlockHeight: 13,
+    },
+  ])
+})
+
+test('computeFees uses the gas price of the mint transaction', (t) => {
+  const tx = (id, txType, fields) => ({ id, blockHeight: 10, txType, status: 1, time: 0, ...fields })
+  const receipt = (txId, receiptType, fields) => ({
+    receiptIndex: 0,
+    txId,
+    txStatus: 1,
+    txType: 0,
+    blockHeight: 10,
+    receiptType,
+    ...fields,
+  })
+  const scriptResult = (txId, gasUsed, result) => receipt(txId, 9, { gasUsed, result })
+  const input = (txId, inputType, fields) => ({ txId, txStatus: 1, txType: 0, blockHeight: 10, inputType, ...fields })
+  const output = (txId, outputType, amount, assetId = hash(0)) => ({
+    txId,
+    txStatus: 1,
+    txType: 0,
+    blockHeight: 10,
+    outputType,
+    amount,
+    assetId,
+  })
+  const report = computeFees(
+    {
+      blocks: [],
+      transactions: [
+        tx(hash(1), 0, { policiesTip: 5, policiesMaxFee: 1000 }),
+        tx(hash(2), 0, { policiesMaxFee: 10 }),
+        tx(hash(3), 2, { mintGasPrice: 2, mintAmount: 1000 }),
+      ],
+      receipts: [
+        // the script sends 60 into a contract and the contract transfers 20 to a variable output
+        receipt(hash(1), 0, { contractId: hash(0), to: hash(7), amount: 60n, assetId: hash(0) }),
+        receipt(hash(1), 8, { contractId: hash(7), toAddress: hash(8), amount: 20n, assetId: hash(0) }),
+        scriptResult(hash(1), 100, 0),
+        // rolled back by the revert
+        receipt(hash(2), 0, { contractId: hash(0), to: hash(7), amount: 100n, assetId: hash(0) }),
+        scriptResult(hash(2), 30, 1),
+      ],
+      inputs: [
+        input(hash(1), 0, { amount: 1000n, assetId: hash(0), predicateGasUsed: 50 }),
+        input(hash(1), 0, { amount: 70n, assetId: hash(9) }),
+        input(hash(2), 2, { amount: 500n }),
+      ],
+      outputs: [
+        output(hash(1), 2, 800n),
+        output(hash(1), 3, 20n),
+        output(hash(1), 2, 70n, hash(9)),
+        output(hash(2), 2, 490n),
+      ],
+    },
+    { gasPriceFactor: 3 },
+  )
+
+  t.deepEqual(
+    report.transactions.map((f) => [f.gasUsed, f.executionFee, f.executionFeePlusTip, f.fee]),
+    [
+      [150, 100n, 105n, 140n],
+      [30, 20n, 10n, 10n],
+    ],
+  )
+  t.deepEqual(report.blocks, [
+    {
+      blockHeight: 10,
+      gasPrice: 2,
+      transactionCount: 2,
+      gasUsed: 180,
+      tips: 5n,
+      executionFeesPlusTips: 115n,
+      fees: 150n,
+      feesCollected: 1000n,
+    },
+  ])
 })
@@ -3,13 +3,510 @@
 
 /* auto-generated by NAPI-RS */
 
+/** A Mint or Burn receipt along with the asset it changed the supply of. */
+export interface MintBurnEvent {
+  /** "mint" or "burn" */
+  kind: string
+  /** Contract that minted or burned the asset */
+  contractId: string
+  /** Sub id of the asset within the contract */
+  subId: string
+  /** Id of the asset, sha256 of the contract id and the sub id */
+  assetId: string
+  /** Amount that was minted or burned */
+  amount: bigint
+  txId: string
+  blockHeight: number
+  receiptIndex: number
+}
+/** Options for computing balance histories. */
+export interface BalanceOptions {
+  /**
+   * Also return the balance every `snapshot_interval` blocks, counted from the start of
+   * the block range
+   */
+  snapshotInterval?: number
+  /** Fetch sub-ranges of the block range in parallel */
+  parallel?: ParallelOptions
+}
+/** Change of a balance within a block. */
+export interface BalanceChange {
+  blockHeight: number
+  /** Net amount that was received minus the amount that was sent in the block */
+  delta: bigint
+  /** Balance after the block */
+  balance: bigint
+}
+/** Balance at the end of a block. */
+export interface BalanceSnapshot {
+  blockHeight: number
+  balance: bigint
+}
+/**
+ * Balance of an asset held by an address or contract over a block range.
+ *
+ * Balances start at zero at the start of the block range, so they are only absolute
+ * balances if the range starts at the genesis block.
+ */
+export interface BalanceHistory {
+  /** Address or contract that holds the asset */
+  address: string
+  assetId: string
+  /** Changes ordered by block, blocks that didn't change the balance are skipped */
+  changes: Array<BalanceChange>
+  /** Balance at the end of every snapshot block, empty if no snapshot interval was given */
+  snapshots: Array<BalanceSnapshot>
+}
+/**
+ * A message sent between the base layer and Fuel.
+ *
+ * Deposits and withdrawals are reconciled by their nonce.
+ */
+export interface BridgeMessage {
+  /**
+   * "withdrawal" for MessageOut receipts sent to the base layer, "deposit" for
+   * InputMessage inputs that consume a message sent from the base layer
+   */
+  direction: string
+  /** Nonce of the message as 32 bytes of hex */
+  nonce: string
+  /** The address of the message sender. */
+  sender?: string
+  /** The address of the message recipient. */
+  recipient?: string
+  /** Amount of base asset sent with the message */
+  amount?: bigint
+  /** Hash of the message data, only set for withdrawals */
+  digest?: string
+  /** The message data. */
+  data?: string
+  txId: string
+  blockHeight: number
+  /** Index of the MessageOut receipt, only set for withdrawals */
+  receiptIndex?: number
+}
+/** Execution tree of a single transaction. */
+export interface CallTree {
+  txId: string
+  blockHeight: number
+  /** Execution of the script, the contract calls made by the script are its children */
+  root: CallNode
+  /** ScriptResult receipt of the transaction if it was among the receipts */
+  scriptResult?: Receipt
+}
+/** A call frame in the execution of a transaction. */
+export interface CallNode {
+  /** Contract that was called, not set for the script */
+  contractId?: string
+  /** Contract that made the call, not set if the script made it */
+  caller?: string
+  /** Index of the Call receipt that started this frame */
+  receiptIndex?: number
+  /** Amount of coins forwarded with the call */
+  amount?: bigint
+  /** Asset id of the coins forwarded with the call */
+  assetId?: string
+  /** Gas forwarded to the call */
+  gas?: number
+  /** Function selector of the call */
+  param1?: bigint
+  /** Argument of the call */
+  param2?: bigint
+  /**
+   * "success" if the frame returned, "revert" or "panic" if it failed, "unfinished" if
+   * it was unwound by a failure in a nested call or its receipts are missing
+   */
+  outcome: string
+  /** Return or ReturnData receipt that ended the frame */
+  returnValue?: Receipt
+  /** Revert or Panic receipt that ended the frame */
+  failure?: Receipt
+  /** Log and LogData receipts emitted in this frame */
+  logs: Array<Receipt>
+  /** Transfer and TransferOut receipts emitted in this frame */
+  transfers: Array<Receipt>
+  /** Other receipts emitted in this frame, e.g. Mint, Burn or MessageOut */
+  receipts: Array<Receipt>
+  /** Calls made from this frame in order */
+  calls: Array<CallNode>
+}
 export interface Config {
   /** Url of the source hypersync instance */
   url: string
   /** Optional bearer_token to put into http requests made to source hypersync instance */
   bearerToken?: string
+  /** Name of an environment variable to read the bearer token from before every request */
+  bearerTokenEnv?: string
+  /** Path of a file to read the bearer token from, the file is read again when it changes */
+  bearerTokenFile?: string
   /** Timout treshold for a single http request in milliseconds, default is 30 seconds (30_000ms) */
   httpReqTimeoutMillis?: number
+  /**
+   * Label to register the statistics of this client under in the process wide metrics registry.
+   * Statistics are only collected if this is set, see `renderMetrics`.
+   * Clients that use the same label share their statistics.
+   */
+  metricsLabel?: string
+  /** Static headers to add to every http request made to the source hypersync instance */
+  httpHeaders?: Record<string, string>
+  /** Appended to the user agent of the client, e.g. the name and version of the application */
+  userAgentSuffix?: string
+  /**
+   * Proxies to send the http requests through.
+   * Proxy settings from the environment are ignored if this is set.
+   */
+  proxy?: ProxyConfig
+  /** Custom certificate authorities and client certificate for mTLS */
+  tls?: TlsConfig
+  /**
+   * Additional instances to fail over to when a request fails with a retryable error.
+   * `url` is used as an endpoint with priority 0 and weight 1.
+   */
+  endpoints?: Array<EndpointConfig>
+  /**
+   * Endpoints whose reported height is more than this many blocks behind the highest height
+   * reported by any endpoint are only used if no other endpoint is available.
+   */
+  endpointMaxLagBlocks?: number
+  /** Maximum number of requests to send per second, unlimited by default */
+  maxRequestsPerSecond?: number
+  /**
+   * Number of requests that can be sent at once before `maxRequestsPerSecond` kicks in,
+   * default is 1
+   */
+  requestBurst?: number
+  /** Maximum number of requests that can be in flight at the same time, unlimited by default */
+  maxConcurrentRequests?: number
+  /**
+   * Clients with the same rate limit group share their rate limit and concurrency cap.
+   * The limits of the first client created in a group apply to the whole group.
+   */
+  rateLimitGroup?: string
+  /**
+   * Number of times to retry a request that every endpoint rejected with HTTP 429 or 503
+   * before failing with a RateLimited error, default is 3.
+   * Requests are not retried if the server asks to wait for more than a minute.
+   */
+  rateLimitRetries?: number
+  /** Directory to cache query responses in. Caching is disabled if this is not set. */
+  cacheDir?: string
+  /**
+   * Maximum total size of the cached responses in bytes, default is 1GiB.
+   * Least recently used responses are evicted first.
+   */
+  cacheMaxBytes?: number
+  /**
+   * Responses are only cached if they end at least this many blocks below the archive height,
+   * default is 100
+   */
+  cacheFinalityMarginBlocks?: number
+  /**
+   * "record" to save every request and response to `fixturesDir`, "replay" to serve the
+   * saved responses in order without touching the network.
+   * Replaying fails with a diff if a request doesn't match the recorded one.
+   */
+  fixturesMode?: string
+  /** Directory of the recorded fixtures. Existing fixtures are removed when recording. */
+  fixturesDir?: string
+  /**
+   * Number of rows that each page of a paginated operation like `createParquetFolder` should
+   * have. The block range and `maxNumBlocks`/`maxNumTransactions` of the requests are adapted
+   * to the density of the pages received so far.
+   */
+  targetPageRows?: number
+  /**
+   * Size in bytes that the response of each page of a paginated operation should have,
+   * pages are sized to meet both targets if `targetPageRows` is also set.
+   */
+  targetPageBytes?: number
+}
+export interface EndpointConfig {
+  /** Url of the hypersync instance */
+  url: string
+  /** Bearer token to use for this instance instead of the one in the client config */
+  bearerToken?: string
+  /** Endpoints with a lower priority are preferred, default is 0 */
+  priority?: number
+  /**
+   * Relative share of the requests that this endpoint gets among the healthy
+   * endpoints of the same priority, default is 1
+   */
+  weight?: number
+}
+export interface ProxyConfig {
+  /** Proxy url to use for http requests */
+  http?: string
+  /** Proxy url to use for https requests */
+  https?: string
+  /**
+   * Proxy url to use for all requests.
+   * Supports socks5:// and socks5h:// urls in addition to http(s) proxies.
+   */
+  all?: string
+  /**
+   * Hosts, domains or ip ranges that should be reached without going through the proxy,
+   * e.g. "localhost", ".internal.example.com" or "10.0.0.0/8"
+   */
+  noProxy?: Array<string>
+}
+export interface TlsConfig {
+  /** Path to a PEM file with certificate authorities to trust in addition to the built in ones */
+  caBundlePath?: string
+  /** Path to a PEM file with the client certificate chain to present for mTLS */
+  clientCertPath?: string
+  /** Path to a PEM file with the private key of the client certificate */
+  clientKeyPath?: string
+}
+/** Options of `presetQueryGetContractDeployments`. */
+export interface ContractDeploymentFilter {
+  /** Only return deployments of these contracts */
+  contractIds?: Array<string>
+  /** Return the bytecode of the deployed contracts, default is false */
+  includeBytecode?: boolean
+}
+/** A contract that was deployed by a Create transaction. */
+export interface ContractDeployment {
+  contractId: string
+  /** Initial state root of the contract */
+  stateRoot?: string
+  /** Root of the contract bytecode */
+  bytecodeRoot?: string
+  /** Salt that was used to derive the contract id */
+  salt?: string
+  /** Create transaction that deployed the contract */
+  txId: string
+  blockHeight: number
+  /** Time the deploying transaction was executed */
+  time?: number
+  /**
+   * Bytecode of the contract if it was requested, read from the witness at the
+   * `bytecode_witness_index` of the transaction
+   */
+  bytecode?: string
+  /**
+   * Set if the bytecode was requested but couldn't be read. The server only returns the
+   * first witness of a transaction, so the bytecode is only available if its witness
+   * index is 0.
+   */
+  bytecodeUnavailable: boolean
+}
+/**
+ * Details of an error that was caused by the server rate limiting the client.
+ *
+ * Errors thrown because of rate limiting have the code `RateLimited` and these properties.
+ */
+export interface RateLimited {
+  /** Http status code of the last response, 429 or 503 */
+  status: number
+  /**
+   * Wait suggested by the server in milliseconds, not set if the server didn't send
+   * a `Retry-After` header
+   */
+  retryAfterMs?: number
+}
+/** Consensus parameters that are needed to turn gas into fees. */
+export interface FeeParameters {
+  /** Divisor of the gas price, the fee of `gas` is `ceil(gas * gas_price / gas_price_factor)` */
+  gasPriceFactor: number
+  /** Asset the fees are paid in, the zero asset id if not set */
+  baseAssetId?: string
+}
+/** Gas and fee of a transaction. */
+export interface TransactionFee {
+  txId: string
+  blockHeight: number
+  /** The type of the transaction */
+  txType: number
+  /** The status type of the transaction */
+  status: number
+  /** Gas price of the block, not set if the mint transaction of the block is missing */
+  gasPrice?: number
+  /** Gas used by the script, from the ScriptResult receipt */
+  scriptGasUsed?: number
+  /** Gas used by the predicates of the inputs */
+  predicateGasUsed: number
+  /** Script and predicate gas */
+  gasUsed: number
+  tip: number
+  maxFee?: number
+  /** Fee of the gas used, not set if the gas price is unknown */
+  executionFee?: bigint
+  /**
+   * Lower bound of the fee: the execution fee plus tip, capped at the max fee of the
+   * transaction. The fee for the size of the transaction is left out.
+   */
+  executionFeePlusTip?: bigint
+  /**
+   * The fee paid, the base asset spent by the inputs that didn't end up in the outputs or
+   * in contracts. Not set if the response has no inputs of the transaction.
+   */
+  fee?: bigint
+}
+/** Gas and fees of a block. */
+export interface BlockFees {
+  blockHeight: number
+  gasPrice?: number
+  /** Number of transactions in the block excluding the mint transaction */
+  transactionCount: number
+  gasUsed: number
+  tips: bigint
+  /** Sum of `execution_fee_plus_tip` of the transactions that have one, a lower bound of the fees */
+  executionFeesPlusTips: bigint
+  /** Sum of `fee` of the transactions that have one */
+  fees: bigint
+  /** Amount of the mint transaction, the total the block producer collected */
+  feesCollected?: bigint
+}
+/** Fees of the transactions and blocks of a response. */
+export interface FeeReport {
+  /** Transactions in the order of the response, mint transactions are skipped */
+  transactions: Array<TransactionFee>
+  /** Blocks ordered by height */
+  blocks: Array<BlockFees>
+}
+/** A transaction along with its block and everything it produced. */
+export interface TransactionContext {
+  transaction: Transaction
+  /** Header of the block the transaction is in, not set if the response didn't contain it */
+  block?: Block
+  /** Receipts of the transaction ordered by receipt index */
+  receipts: Array<Receipt>
+  /** Inputs of the transaction in the order they appear in the transaction */
+  inputs: Array<Input>
+  /** Outputs of the transaction in the order they appear in the transaction */
+  outputs: Array<Output>
+}
+/** Client side measurements of a single request to the hyperfuel instance. */
+export interface RequestMetrics {
+  /** Url of the instance that served the request, empty if it was served from the cache. */
+  endpoint: string
+  /** Whether the response was served from the local response cache. */
+  cacheHit: boolean
+  /** Time spent waiting for the client side rate limit or concurrency cap in milliseconds. */
+  rateLimitWaitMs: number
+  /**
+   * Time spent resolving the host name in milliseconds.
+   * Only set if a new connection had to be opened for this request.
+   */
+  dnsTimeMs?: number
+  /** Whether a new connection was opened for this request instead of reusing a pooled one. */
+  newConnection: boolean
+  /**
+   * Time spent opening the tcp connection in milliseconds.
+   * Only set if a new https connection was opened for this request, the end of the
+   * connect isn't observable for plain http connections.
+   */
+  connectTimeMs?: number
+  /**
+   * Time spent on the tls handshake in milliseconds.
+   * Only set if a new https connection was opened for this request.
+   */
+  tlsHandshakeTimeMs?: number
+  /**
+   * Time from sending the request until the response headers arrived in milliseconds.
+   * This includes the dns, connect and tls handshake times if a new connection was opened.
+   */
+  timeToFirstByteMs: number
+  /** Time spent reading the response body in milliseconds. */
+  transferTimeMs: number
+  /** Size of the response body as it was sent over the network (packed capnp). */
+  responseBytes: number
+  /** Size of the response message after unpacking. */
+  responseBytesUncompressed: number
+  /** Time spent decoding the response into rust types in milliseconds. */
+  decodeTimeMs: number
+  /**
+   * Time spent converting the decoded response into the response types in milliseconds.
+   * The conversion into javascript values happens after the response is handed over to
+   * node, it is only counted in `napiConversionTimeMs` of the client metrics.
+   */
+  conversionTimeMs: number
+}
+/** Cumulative measurements of all requests made by a client. */
+export interface ClientMetrics {
+  /** Number of requests made. */
+  requests: number
+  /** Number of requests that had to open a new connection. */
+  newConnections: number
+  /** Number of requests that were served from the local response cache. */
+  cacheHits: number
+  rateLimitWaitMs: number
+  dnsTimeMs: number
+  connectTimeMs: number
+  tlsHandshakeTimeMs: number
+  timeToFirstByteMs: number
+  transferTimeMs: number
+  responseBytes: number
+  responseBytesUncompressed: number
+  decodeTimeMs: number
+  conversionTimeMs: number
+  /** Time spent converting responses into javascript values in milliseconds. */
+  napiConversionTimeMs: number
+}
+/** Data served by a `MockHyperfuelServer`, in the same shape `getData` returns it. */
+export interface MockData {
+  blocks?: Array<Block>
+  transactions?: Array<Transaction>
+  receipts?: Array<Receipt>
+  inputs?: Array<Input>
+  outputs?: Array<Output>
+}
+export interface MockServerOptions {
+  /** Height reported by the server, default is the highest block height in the data */
+  archiveHeight?: number
+  /** Maximum number of blocks to return in a single response, to force pagination */
+  maxBlocksPerResponse?: number
+  /** Maximum number of transactions to return in a single response, to force pagination */
+  maxTransactionsPerResponse?: number
+  /** Requests without this bearer token are rejected with HTTP 401 */
+  bearerToken?: string
+}
+/** An input that was unlocked by a predicate. */
+export interface PredicateSpend {
+  /** Root of the predicate, the owner of the coin or the recipient of the message */
+  predicateRoot: string
+  /** sha256 of the predicate bytecode */
+  bytecodeHash: string
+  txId: string
+  /** The status type of the transaction */
+  txStatus: number
+  blockHeight: number
+  /** Index of the input in the transaction */
+  inputIndex: number
+  /** The type of the input, 0 for coins and 2 for messages */
+  inputType: number
+  /** Coin that was spent, not set for messages */
+  utxoId?: string
+  /** Nonce of the message that was spent, not set for coins */
+  nonce?: string
+  assetId?: string
+  amount?: bigint
+  predicateGasUsed?: number
+  /** Bytecode of the predicate */
+  predicate: string
+  /** Data that was passed to the predicate */
+  predicateData?: string
+}
+/** Amount of an asset. */
+export interface AssetAmount {
+  assetId: string
+  amount: bigint
+}
+/** Activity of the predicates that share the same bytecode. */
+export interface PredicateStats {
+  /** sha256 of the predicate bytecode */
+  bytecodeHash: string
+  /** Roots of the predicates that were spent with this bytecode */
+  predicateRoots: Array<string>
+  spendCount: number
+  /** Number of spends in transactions that failed */
+  failedSpendCount: number
+  /** Gas used by the predicates to validate the spends */
+  totalGasUsed: number
+  /** Amount of each asset that was unlocked by the spends, ordered by asset id */
+  assetsMoved: Array<AssetAmount>
+  firstBlockHeight: number
+  lastBlockHeight: number
 }
 export interface ReceiptSelection {
   rootContractId?: Array<string>
@@ -101,6 +598,20 @@ export interface Query {
    */
   maxNumTransactions?: number
 }
+export interface BatchOptions {
+  /** Maximum number of queries to run at the same time, default is 4 */
+  concurrency?: number
+}
+export interface ParallelOptions {
+  /** Maximum number of sub-ranges to fetch at the same time, default is 4 */
+  concurrency?: number
+  /**
+   * Number of blocks in the first sub-ranges. Later sub-ranges are sized by how fast the
+   * server progressed through the earlier ones. By default the range is split into
+   * 4 sub-ranges per concurrent request.
+   */
+  initialChunkBlocks?: number
+}
 export interface QueryResponseTyped {
   /** Current height of the source hypersync instance */
   archiveHeight?: number
@@ -114,6 +625,17 @@ export interface QueryResponseTyped {
   totalExecutionTime: number
   /** Response data */
   data: QueryResponseDataTyped
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+/** Outcome of a single query of `getDataBatch`, exactly one of `response` and `error` is set. */
+export interface BatchResult {
+  /** Response of the query if it succeeded */
+  response?: QueryResponseTyped
+  /** Error message if the query failed, same as the message `getData` would throw */
+  error?: string
+  /** Rate limit details if the query failed because the server rate limited the client */
+  rateLimited?: RateLimited
 }
 export interface QueryResponseDataTyped {
   blocks: Array<Block>
@@ -135,6 +657,120 @@ export interface LogResponse {
   totalExecutionTime: number
   /** Response data */
   data: Array<LogContext>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface TransactionsResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so
+   * the caller should continue the query from this block if they
+   * didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Transactions with their block, receipts, inputs and outputs */
+  data: Array<TransactionContext>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface TransferResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so
+   * the caller should continue the query from this block if they
+   * didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Transfers ordered by block and transaction */
+  data: Array<Transfer>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface MintBurnResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so
+   * the caller should continue the query from this block if they
+   * didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Mint and burn events ordered by block and receipt index */
+  data: Array<MintBurnEvent>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface ContractDeploymentResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so
+   * the caller should continue the query from this block if they
+   * didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Deployed contracts ordered by block */
+  data: Array<ContractDeployment>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface BridgeMessageResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /**
+   * Next block to query for, the responses are paginated so
+   * the caller should continue the query from this block if they
+   * didn't get responses up to the to_block they specified in the Query.
+   */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the query. */
+  totalExecutionTime: number
+  /** Deposits and withdrawals ordered by block */
+  data: Array<BridgeMessage>
+  /** Client side metrics of the request */
+  metrics: RequestMetrics
+}
+export interface BalanceResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /** Block the balances were computed up to, exclusive */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the queries. */
+  totalExecutionTime: number
+  /** Balance histories ordered by address and asset id */
+  data: Array<BalanceHistory>
+}
+export interface PredicateActivityResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /** Block the activity was collected up to, exclusive */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the queries. */
+  totalExecutionTime: number
+  /** Predicate spends ordered by block */
+  spends: Array<PredicateSpend>
+  /** Stats of each predicate bytecode ordered by bytecode hash */
+  stats: Array<PredicateStats>
+}
+export interface UtxoLifecycleResponse {
+  /** Current height of the source hypersync instance */
+  archiveHeight?: number
+  /** Block the lifecycles were tracked up to, exclusive */
+  nextBlock: number
+  /** Total time it took the hypersync instance to execute the queries. */
+  totalExecutionTime: number
+  /** Coins ordered by the block they were created in */
+  data: Array<UtxoLifecycle>
 }
 /**
  * Contains all the fields needed for decoding plus some additional fields
@@ -158,6 +794,53 @@ export interface LogContext {
   digest?: string
   data?: string
 }
+/**
+ * A finished span of a client operation.
+ *
+ * Ids follow the W3C trace context format so spans can be handed to an OpenTelemetry exporter.
+ */
+export interface SpanData {
+  /** Name of the operation */
+  name: string
+  /** Hex encoded 16 byte trace id */
+  traceId: string
+  /** Hex encoded 8 byte span id */
+  spanId: string
+  /** Hex encoded 8 byte id of the parent span, not set for a root span without a traceparent */
+  parentSpanId?: string
+  /** Start time in milliseconds since unix epoch */
+  startTimeMs: number
+  /** End time in milliseconds since unix epoch */
+  endTimeMs: number
+  attributes: Record<string, any>
+  /** Error message if the operation failed */
+  error?: string
+}
+/** A movement of an asset, normalized from a receipt, coin input or coin output. */
+export interface Transfer {
+  /**
+   * "transfer" for Transfer receipts (contract to contract), "transfer_out" for
+   * TransferOut receipts (contract to address), "coin_input" for spent coins,
+   * "coin_output", "change_output" or "variable_output" for created coins
+   */
+  kind: string
+  /** Contract or address the asset moved from, not set for outputs */
+  from?: string
+  /** Contract or address the asset moved to, not set for inputs */
+  to?: string
+  assetId?: string
+  amount?: bigint
+  txId: string
+  /** The status type of the transaction */
+  txStatus: number
+  blockHeight: number
+  /** Index of the receipt for transfer and transfer_out records */
+  receiptIndex?: number
+  /** Index of the input in the transaction for coin_input records */
+  inputIndex?: number
+  /** Index of the output in the transaction for output records */
+  outputIndex?: number
+}
 /** The block header contains metadata about a certain block. */
 export interface Block {
   /** String of the header */
@@ -408,9 +1091,107 @@ export interface Output {
   /** for ContractCreated type: The contract that was created. */
   contract?: string
 }
+/** The output that a coin input spends. */
+export interface UtxoId {
+  /** Transaction that created the output */
+  txId: string
+  /** Index of the output in the transaction, not set if the utxo id doesn't contain it */
+  outputIndex?: number
+}
+/** A coin from its creation to the point it was spent. */
+export interface UtxoLifecycle {
+  /** Transaction that created the coin */
+  txId: string
+  /** Index of the output that created the coin */
+  outputIndex?: number
+  /** Owner of the coin */
+  owner?: string
+  assetId?: string
+  amount?: bigint
+  /** Block the coin was created in, not set if it was created before the block range */
+  createdBlockHeight?: number
+  /** Transaction that spent the coin, not set if it is unspent at the end of the block range */
+  spentTxId?: string
+  /** Block the coin was spent in */
+  spentBlockHeight?: number
+}
+/**
+ * Render the statistics of all clients that were created with a `metricsLabel`
+ * in OpenMetrics text format.
+ */
+export declare function renderMetrics(): string
+/**
+ * Rebuild the call tree of every transaction from its receipts.
+ *
+ * Each call frame carries the logs and transfers emitted in it, its return value and
+ * whether it reverted or panicked. The receipts of a transaction have to be complete,
+ * so don't filter them by type or contract in the query.
+ */
+export declare function buildCallTrees(receipts: Array<Receipt>): Array<CallTree>
+/**
+ * Compute the gas used, tip and fee of every transaction in the response data, along with
+ * the totals of each block.
+ *
+ * The gas price of a block is taken from its mint transaction, so the data has to contain
+ * the mint transactions with `mint_gas_price` and `mint_amount` selected. Transactions need
+ * `id`, `block_height`, `tx_type`, `status`, `policies_tip` and `policies_max_fee`.
+ * ScriptResult receipts with `gas_used` and `result` and inputs with `predicate_gas_used`
+ * provide the gas used.
+ *
+ * The fee paid is computed from the base asset of the inputs and outputs, so all inputs and
+ * outputs of the transactions need `input_type`/`output_type`, `amount`, `asset_id` and the
+ * `data` of message inputs. Call, Transfer, TransferOut and MessageOut receipts with
+ * `contract_id`, `sender`, `amount` and `asset_id` account for the base asset moved into and
+ * out of contracts. The execution fee plus tip leaves out the fee for the size of a
+ * transaction and is only a lower bound.
+ */
+export declare function computeFees(data: QueryResponseDataTyped, params: FeeParameters): FeeReport
+/**
+ * Decode a utxo id into the transaction that created the coin and the index of its output.
+ *
+ * The output index isn't set for 32 byte utxo ids, which only contain the transaction id.
+ */
+export declare function decodeUtxoId(utxoId: string): UtxoId
+/**
+ * A local http server that implements the hyperfuel api on top of in-memory data.
+ *
+ * Point `Config.url` at `url` to test code built on the client without network access.
+ */
+export class MockHyperfuelServer {
+  /** Start serving the given data on a random local port. */
+  static start(data: MockData, options?: MockServerOptions | undefined | null): Promise<MockHyperfuelServer>
+  /** Url to put into `Config.url` */
+  get url(): string
+  /**
+   * Change the height reported by the server, e.g. to simulate a lagging instance.
+   * Data above the height is not served.
+   */
+  setArchiveHeight(height?: number | undefined | null): void
+  /** Number of requests the server received */
+  requestCount(): number
+  /** Stop the server */
+  close(): void
+}
 export class HyperfuelClient {
   /** Create a new client with given config */
   static new(cfg: Config): HyperfuelClient
+  /**
+   * Register a callback that receives the tracing spans of this client's operations
+   * after they finish.
+   *
+   * Span ids follow the W3C trace context format, so the spans can be forwarded to an
+   * OpenTelemetry exporter.
+   */
+  onSpan(callback: (span: SpanData) => void): void
+  /**
+   * Get bearer tokens from the given callback instead of the config.
+   *
+   * The callback is called before the first request and again whenever the server rejects
+   * the token with HTTP 401, in which case the request is retried once with the new token.
+   */
+  setBearerTokenProvider(callback: () => string | Promise<string>): void
+  /** Get cumulative client side metrics of all requests made by this client */
+  metrics(): ClientMetrics
   /** Get the height of the source hyperfuel instance */
   getHeight(): Promise<number>
   /**
@@ -419,14 +1200,21 @@ export class HyperfuelClient {
    * On an error from the source hyperfuel instance, sleeps for
    * 1 second (increasing by 1 each failure up to max of 5 seconds)
    * and retries query until success.
+   * Fails without retrying on errors that retrying can't fix, like a request the server
+   * rejected, a rate limit that outlasted the retries of the transport or a missing fixture.
    */
   getHeightWithRetry(): Promise<number>
   /**
    * Create a parquet file by executing a query.
    *
    * Path should point to a folder that will contain the parquet files in the end.
+   *
+   * If a W3C traceparent is given, the spans of this operation continue that trace.
+   *
+   * If `parallel` is given, the block range is split into sub-ranges that are fetched
+   * in parallel. The files are still written in block order.
    */
-  createParquetFolder(query: Query, path: string): Promise<void>
+  createParquetFolder(query: Query, path: string, traceparent?: string | undefined | null, parallel?: ParallelOptions | undefined | null): Promise<void>
   /**
    * Send a query request to the source hyperfuel instance.
    *
@@ -436,15 +1224,38 @@ export class HyperfuelClient {
    * and applies the field selection to all these loaded transactions.  So your query will return the data you
    * want plus additional data from the loaded transactions.  This functionality is in case you want to associate
    * receipts, inputs, or outputs with eachother.
+   *
+   * If a W3C traceparent is given, the spans of this operation continue that trace.
    */
-  getData(query: Query): Promise<QueryResponseTyped>
+  getData(query: Query, traceparent?: string | undefined | null): Promise<QueryResponseTyped>
+  /**
+   * Send multiple queries at once, same as calling `getData` with each of them.
+   *
+   * Up to `options.concurrency` queries run at the same time (default is 4). Results are
+   * returned in the order of the queries, a query that fails doesn't fail the others.
+   *
+   * If a W3C traceparent is given, the spans of every query continue that trace.
+   */
+  getDataBatch(queries: Array<Query>, options?: BatchOptions | undefined | null, traceparent?: string | undefined | null): Promise<Array<BatchResult>>
   /**
    * Send a query request to the source hyperfuel instance.
    *
    * Returns a query response that which contains structured data that doesn't include any inputs, outputs,
    * and receipts that don't exactly match the query's input, outout, or receipt selection.
+   *
+   * If a W3C traceparent is given, the spans of this operation continue that trace.
+   */
+  getSelectedData(query: Query, traceparent?: string | undefined | null): Promise<QueryResponseTyped>
+  /**
+   * Send a query request to the source hyperfuel instance.
+   *
+   * Returns the transactions of the response, each with its block header and its receipts,
+   * inputs and outputs nested under it. The fields needed to join the data are added to
+   * the field selection.
+   *
+   * If a W3C traceparent is given, the spans of this operation continue that trace.
    */
-  getSelectedData(query: Query): Promise<QueryResponseTyped>
+  getTransactions(query: Query, traceparent?: string | undefined | null): Promise<TransactionsResponse>
   /**
    * Send a query request to the source hyperfuel instance.
    *
@@ -458,4 +1269,95 @@ export class HyperfuelClient {
    * NOTE: this function is experimental and might be removed in future versions.
    */
   presetQueryGetLogs(emittingContracts: Array<string>, fromBlock: number, toBlock?: number | undefined | null): Promise<LogResponse>
+  /**
+   * Send a query request to the source hyperfuel instance.
+   *
+   * Returns every movement of the given assets from or to any of the given addresses or
+   * contracts within the block range, normalized from Transfer and TransferOut receipts,
+   * coin inputs and coin, change and variable outputs. Lists that are not given or empty
+   * match everything.
+   * Transfers to contracts are only found in transactions that match otherwise, since
+   * receipts can't be selected by their recipient contract.
+   * If no 'to_block' is specified, query will run to the head of the chain.
+   * Transfers of failed transactions are left out unless `includeFailed` is set.
+   *
+   * NOTE: this function is experimental and might be removed in future versions.
+   */
+  presetQueryGetTransfers(assetIds: Array<string> | undefined | null, addresses: Array<string> | undefined | null, contracts: Array<string> | undefined | null, fromBlock: number, toBlock?: number | undefined | null, includeFailed?: boolean | undefined | null): Promise<TransferResponse>
+  /**
+   * Send a query request to the source hyperfuel instance.
+   *
+   * Returns all Mint and Burn receipts of the specified contracts within the block range,
+   * along with the id of the asset they minted or burned. Receipts without an amount are
+   * left out.
+   * If no 'to_block' is specified, query will run to the head of the chain.
+   * This query doesn't return any receipts that were a part of a failed transaction.
+   *
+   * NOTE: this function is experimental and might be removed in future versions.
+   */
+  presetQueryGetMintsBurns(contracts: Array<string>, fromBlock: number, toBlock?: number | undefined | null): Promise<MintBurnResponse>
+  /**
+   * Send a query request to the source hyperfuel instance.
+   *
+   * Returns the contracts deployed by successful Create transactions within the block range.
+   * If no 'to_block' is specified, query will run to the head of the chain.
+   *
+   * NOTE: this function is experimental and might be removed in future versions.
+   */
+  presetQueryGetContractDeployments(fromBlock: number, toBlock?: number | undefined | null, filters?: ContractDeploymentFilter | undefined | null): Promise<ContractDeploymentResponse>
+  /**
+   * Send a query request to the source hyperfuel instance.
+   *
+   * Returns the messages sent between the base layer and Fuel within the block range:
+   * withdrawals from MessageOut receipts and deposits from InputMessage inputs.
+   * Only messages from the given senders and to the given recipients are returned
+   * if the lists are given.
+   * If no 'to_block' is specified, query will run to the head of the chain.
+   * This query doesn't return any messages that were a part of a failed transaction.
+   *
+   * NOTE: this function is experimental and might be removed in future versions.
+   */
+  presetQueryGetBridgeMessages(senders: Array<string> | undefined | null, recipients: Array<string> | undefined | null, fromBlock: number, toBlock?: number | undefined | null): Promise<BridgeMessageResponse>
+  /**
+   * Compute the balance history of the given addresses and contracts within the block range.
+   *
+   * Returns the net change and the running balance for every block that changed a balance,
+   * per address or contract and asset. Balances start at zero at 'from_block', so they are
+   * only absolute balances if 'from_block' is 0.
+   * Coin inputs and outputs count towards the balances of addresses, Transfer and
+   * TransferOut receipts count towards the balances of contracts. Only successful
+   * transactions are counted.
+   * Transfers to contracts are only found in transactions that match otherwise, since
+   * receipts can't be selected by their recipient contract.
+   * Every asset is tracked if no asset ids are given.
+   * If no 'to_block' is specified, balances are computed up to the head of the chain.
+   * If `options.parallel` is given, the block range is split into sub-ranges that are
+   * fetched in parallel. Pages are still processed in block order.
+   */
+  computeBalances(addresses: Array<string> | undefined | null, contracts: Array<string> | undefined | null, assetIds: Array<string> | undefined | null, fromBlock: number, toBlock?: number | undefined | null, options?: BalanceOptions | undefined | null): Promise<BalanceResponse>
+  /**
+   * Collect the spends of the given predicate roots within the block range.
+   *
+   * Returns every coin and message input that was unlocked by one of the predicates,
+   * with its bytecode, data and the gas it used, along with stats for each predicate
+   * bytecode.
+   * If no 'to_block' is specified, activity is collected up to the head of the chain.
+   * If `parallel` is given, the block range is split into sub-ranges that are fetched
+   * in parallel. Pages are still processed in block order.
+   */
+  getPredicateActivity(predicateRoots: Array<string>, fromBlock: number, toBlock?: number | undefined | null, parallel?: ParallelOptions | undefined | null): Promise<PredicateActivityResponse>
+  /**
+   * Track the coins of the given owners or the given utxos within the block range.
+   *
+   * Returns every matching coin that was created or spent within the range, along with the
+   * transaction that created it and the transaction that spent it. Coins that were created
+   * before the range only have the spending side set, coins that are still unspent at the
+   * end of the range only have the creating side set.
+   * The coins of every owner are scanned if no owners are given, so the range should be
+   * kept short when tracking utxos only.
+   * If no 'to_block' is specified, the lifecycles are tracked up to the head of the chain.
+   * If `parallel` is given, the block range is split into sub-ranges that are fetched
+   * in parallel. Pages are still processed in block order.
+   */
+  getUtxoLifecycles(owners: Array<string> | undefined | null, utxoIds: Array<string> | undefined | null, fromBlock: number, toBlock?: number | undefined | null, parallel?: ParallelOptions | undefined | null): Promise<UtxoLifecycleResponse>
 }
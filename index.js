@@ -310,6 +310,11 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { HyperfuelClient } = nativeBinding
+const { MockHyperfuelServer, renderMetrics, buildCallTrees, computeFees, decodeUtxoId, HyperfuelClient } = nativeBinding
 
+module.exports.MockHyperfuelServer = MockHyperfuelServer
+module.exports.renderMetrics = renderMetrics
+module.exports.buildCallTrees = buildCallTrees
+module.exports.computeFees = computeFees
+module.exports.decodeUtxoId = decodeUtxoId
 module.exports.HyperfuelClient = HyperfuelClient
@@ -0,0 +1,60 @@
+use hyperfuel_format::{Hash, Hex, Receipt, ReceiptType};
+use napi::bindgen_prelude::BigInt;
+use ring::digest::{Context, SHA256};
+
+use crate::types::as_i64;
+
+/// A Mint or Burn receipt along with the asset it changed the supply of.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MintBurnEvent {
+    /// "mint" or "burn"
+    pub kind: String,
+    /// Contract that minted or burned the asset
+    pub contract_id: String,
+    /// Sub id of the asset within the contract
+    pub sub_id: String,
+    /// Id of the asset, sha256 of the contract id and the sub id
+    pub asset_id: String,
+    /// Amount that was minted or burned
+    pub amount: BigInt,
+    pub tx_id: String,
+    pub block_height: i64,
+    pub receipt_index: i64,
+}
+
+/// Id of the asset with the given sub id that is minted by the contract.
+pub fn asset_id(contract_id: &Hash, sub_id: &Hash) -> Hash {
+    let mut ctx = Context::new(&SHA256);
+    ctx.update(contract_id.as_ref());
+    ctx.update(sub_id.as_ref());
+    let digest = ctx.finish();
+
+    Hash::try_from(digest.as_ref()).unwrap()
+}
+
+impl MintBurnEvent {
+    /// Convert a Mint or Burn receipt, returns `None` for other receipts or if the receipt
+    /// is missing its contract id, sub id or amount.
+    pub fn from_receipt(receipt: Receipt) -> Option<Self> {
+        let kind = match receipt.receipt_type {
+            ReceiptType::Mint => "mint",
+            ReceiptType::Burn => "burn",
+            _ => return None,
+        };
+        let contract_id = receipt.contract_id?;
+        let sub_id = receipt.sub_id?;
+        let amount: u64 = *receipt.val?;
+
+        Some(Self {
+            kind: kind.into(),
+            asset_id: asset_id(&contract_id, &sub_id).encode_hex(),
+            contract_id: contract_id.encode_hex(),
+            sub_id: sub_id.encode_hex(),
+            amount: amount.into(),
+            tx_id: receipt.tx_id.encode_hex(),
+            block_height: as_i64(receipt.block_height),
+            receipt_index: as_i64(receipt.receipt_index),
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Context, Result};
+use napi::{
+    bindgen_prelude::{Either, Promise},
+    threadsafe_function::{ErrorStrategy, ThreadsafeFunction},
+};
+
+use crate::config::Config;
+
+const REDACTED: &str = "[REDACTED]";
+
+static SECRETS: OnceLock<RwLock<BTreeSet<String>>> = OnceLock::new();
+
+fn secrets() -> &'static RwLock<BTreeSet<String>> {
+    SECRETS.get_or_init(Default::default)
+}
+
+/// Remember a token so it gets redacted from error messages.
+pub fn register_secret(token: &str) {
+    if token.is_empty() {
+        return;
+    }
+    let mut secrets = secrets().write().unwrap();
+    if !secrets.contains(token) {
+        secrets.insert(token.to_owned());
+    }
+}
+
+/// Replace every bearer token the process has used with a placeholder.
+pub fn redact(text: &str) -> String {
+    let secrets = secrets().read().unwrap();
+    let mut text = text.to_owned();
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = text.replace(secret.as_str(), REDACTED);
+        }
+    }
+    text
+}
+
+/// Format an error along with its causes, with the bearer tokens redacted.
+pub fn redact_error(err: &anyhow::Error) -> String {
+    redact(&format!("{:?}", err))
+}
+
+/// Javascript callback that returns a bearer token, or a promise that resolves to one.
+pub type TokenCallback = ThreadsafeFunction<(), ErrorStrategy::Fatal>;
+
+enum Source {
+    None,
+    Static(String),
+    Env(String),
+    File {
+        path: PathBuf,
+        cached: Mutex<Option<(SystemTime, String)>>,
+    },
+    Callback {
+        callback: TokenCallback,
+        cached: tokio::sync::Mutex<Option<String>>,
+    },
+}
+
+/// Provides the bearer token to put into requests.
+///
+/// Tokens from an environment variable are read for every request, tokens from a file are
+/// re-read when the file changes and tokens from a callback are requested once and then only
+/// again after the server rejected the token.
+pub struct TokenProvider {
+    source: RwLock<Arc<Source>>,
+}
+
+impl TokenProvider {
+    pub fn new(cfg: &Config) -> Result<Self> {
+        let source = match (
+            &cfg.bearer_token,
+            &cfg.bearer_token_env,
+            &cfg.bearer_token_file,
+        ) {
+            (None, None, None) => Source::None,
+            (Some(token), None, None) => {
+                register_secret(token);
+                Source::Static(token.clone())
+            }
+            (None, Some(var), None) => Source::Env(var.clone()),
+            (None, None, Some(path)) => Source::File {
+                path: path.into(),
+                cached: Mutex::new(None),
+            },
+            _ => {
+                return Err(anyhow!(
+                    "only one of bearer_token, bearer_token_env and bearer_token_file can be set"
+                ))
+            }
+        };
+
+        Ok(Self {
+            source: RwLock::new(Arc::new(source)),
+        })
+    }
+
+    /// Get tokens from the given callback from now on.
+    pub fn set_callback(&self, callback: TokenCallback) {
+        *self.source.write().unwrap() = Arc::new(Source::Callback {
+            callback,
+            cached: tokio::sync::Mutex::new(None),
+        });
+    }
+
+    /// Whether getting the token again might return a different one.
+    pub fn is_refreshable(&self) -> bool {
+        !matches!(
+            **self.source.read().unwrap(),
+            Source::None | Source::Static(_)
+        )
+    }
+
+    /// Get the current token.
+    pub async fn token(&self) -> Result<Option<String>> {
+        let source = self.source.read().unwrap().clone();
+        match &*source {
+            Source::None => Ok(None),
+            Source::Static(token) => Ok(Some(token.clone())),
+            Source::Env(var) => read_env(var).map(Some),
+            Source::File { path, cached } => {
+                let modified = std::fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .with_context(|| format!("read metadata of token file {}", path.display()))?;
+                if let Some((cached_modified, token)) = &*cached.lock().unwrap() {
+                    if *cached_modified == modified {
+                        return Ok(Some(token.clone()));
+                    }
+                }
+                let token = read_file(path)?;
+                *cached.lock().unwrap() = Some((modified, token.clone()));
+                Ok(Some(token))
+            }
+            Source::Callback { callback, cached } => {
+                let mut cached = cached.lock().await;
+                if let Some(token) = &*cached {
+                    return Ok(Some(token.clone()));
+                }
+                let token = call(callback).await?;
+                *cached = Some(token.clone());
+                Ok(Some(token))
+            }
+        }
+    }
+
+    /// Drop any cached token and get a fresh one.
+    pub async fn refresh(&self) -> Result<Option<String>> {
+        let source = self.source.read().unwrap().clone();
+        match &*source {
+            Source::File { cached, .. } => {
+                cached.lock().unwrap().take();
+            }
+            Source::Callback { cached, .. } => {
+                cached.lock().await.take();
+            }
+            _ => (),
+        }
+        self.token().await
+    }
+}
+
+fn read_env(var: &str) -> Result<String> {
+    let token = std::env::var(var)
+        .with_context(|| format!("read bearer token from environment variable {}", var))?;
+    let token = token.trim().to_owned();
+    register_secret(&token);
+    Ok(token)
+}
+
+fn read_file(path: &PathBuf) -> Result<String> {
+    let token = std::fs::read_to_string(path)
+        .with_context(|| format!("read bearer token from file {}", path.display()))?;
+    let token = token.trim().to_owned();
+    register_secret(&token);
+    Ok(token)
+}
+
+async fn call(callback: &TokenCallback) -> Result<String> {
+    let res: Either<String, Promise<String>> = callback
+        .call_async(())
+        .await
+        .map_err(|e| anyhow!("call bearer token provider: {}", e))?;
+    let token = match res {
+        Either::A(token) => token,
+        Either::B(promise) => promise
+            .await
+            .map_err(|e| anyhow!("bearer token provider rejected: {}", redact(&e.reason)))?,
+    };
+    register_secret(&token);
+    Ok(token)
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use napi::bindgen_prelude::BigInt;
+
+use crate::{
+    query::ParallelOptions,
+    response::QueryResponseDataTyped,
+    transfers::{extract_transfers, TransferFilter},
+};
+
+/// Options for computing balance histories.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct BalanceOptions {
+    /// Also return the balance every `snapshot_interval` blocks, counted from the start of
+    /// the block range
+    pub snapshot_interval: Option<i64>,
+    /// Fetch sub-ranges of the block range in parallel
+    pub parallel: Option<ParallelOptions>,
+}
+
+/// Change of a balance within a block.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BalanceChange {
+    pub block_height: i64,
+    /// Net amount that was received minus the amount that was sent in the block
+    pub delta: BigInt,
+    /// Balance after the block
+    pub balance: BigInt,
+}
+
+/// Balance at the end of a block.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BalanceSnapshot {
+    pub block_height: i64,
+    pub balance: BigInt,
+}
+
+/// Balance of an asset held by an address or contract over a block range.
+///
+/// Balances start at zero at the start of the block range, so they are only absolute
+/// balances if the range starts at the genesis block.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BalanceHistory {
+    /// Address or contract that holds the asset
+    pub address: String,
+    pub asset_id: String,
+    /// Changes ordered by block, blocks that didn't change the balance are skipped
+    pub changes: Vec<BalanceChange>,
+    /// Balance at the end of every snapshot block, empty if no snapshot interval was given
+    pub snapshots: Vec<BalanceSnapshot>,
+}
+
+/// Accumulates the balance changes of the tracked addresses and contracts across the pages
+/// of a query.
+///
+/// Coins count towards the balance of the addresses that own them, Transfer and TransferOut
+/// receipts count towards the balance of the contracts. The receiving address of a
+/// TransferOut is credited by the variable output the receipt creates. Only successful
+/// transactions are counted.
+pub struct BalanceTracker {
+    addresses: HashSet<String>,
+    contracts: HashSet<String>,
+    filter: TransferFilter,
+    snapshot_interval: Option<u64>,
+    /// Net change per block by address and asset
+    deltas: BTreeMap<(String, String), BTreeMap<i64, i128>>,
+}
+
+impl BalanceTracker {
+    /// Track the balances of `addresses` and `contracts` for `asset_ids`, every asset is
+    /// tracked if `asset_ids` is empty.
+    pub fn new(
+        addresses: HashSet<String>,
+        contracts: HashSet<String>,
+        asset_ids: HashSet<String>,
+        options: &BalanceOptions,
+    ) -> Result<Self> {
+        let snapshot_interval = options
+            .snapshot_interval
+            .map(|interval| {
+                u64::try_from(interval)
+                    .ok()
+                    .filter(|&interval| interval > 0)
+                    .ok_or_else(|| anyhow!("snapshot_interval has to be positive"))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            filter: TransferFilter {
+                asset_ids,
+                parties: addresses.union(&contracts).cloned().collect(),
+                include_failed: false,
+            },
+            addresses,
+            contracts,
+            snapshot_interval,
+            deltas: BTreeMap::new(),
+        })
+    }
+
+    /// Add the transfers of a page to the balances.
+    pub fn add(&mut self, data: QueryResponseDataTyped) {
+        for transfer in extract_transfers(data, &self.filter) {
+            let (Some(asset_id), Some(amount)) = (transfer.asset_id, transfer.amount) else {
+                continue;
+            };
+            let amount = amount.get_u64().1 as i128;
+
+            let (parties, credited) = match transfer.kind.as_str() {
+                "transfer" => (&self.contracts, transfer.to),
+                "transfer_out" => (&self.contracts, None),
+                _ => (&self.addresses, transfer.to),
+            };
+            for (party, delta) in [(transfer.from, -amount), (credited, amount)] {
+                let Some(party) = party.filter(|party| parties.contains(party)) else {
+                    continue;
+                };
+                *self
+                    .deltas
+                    .entry((party, asset_id.clone()))
+                    .or_default()
+                    .entry(transfer.block_height)
+                    .or_default() += delta;
+            }
+        }
+    }
+
+    /// Build the histories of the balances that changed within `[from_block, to_block)`.
+    ///
+    /// Histories are ordered by address and asset id.
+    pub fn finish(self, from_block: u64, to_block: u64) -> Vec<BalanceHistory> {
+        let interval = self.snapshot_interval;
+
+        self.deltas
+            .into_iter()
+            .map(|((address, asset_id), deltas)| {
+                let mut balance = 0;
+                let changes: Vec<_> = deltas
+                    .iter()
+                    .filter(|(_, &delta)| delta != 0)
+                    .map(|(&block_height, &delta)| {
+                        balance += delta;
+                        BalanceChange {
+                            block_height,
+                            delta: delta.into(),
+                            balance: balance.into(),
+                        }
+                    })
+                    .collect();
+
+                let snapshots = match interval {
+                    Some(interval) => snapshots(&deltas, from_block, to_block, interval),
+                    None => Vec::new(),
+                };
+
+                BalanceHistory {
+                    address,
+                    asset_id,
+                    changes,
+                    snapshots,
+                }
+            })
+            .filter(|history| !history.changes.is_empty())
+            .collect()
+    }
+}
+
+fn snapshots(
+    deltas: &BTreeMap<i64, i128>,
+    from_block: u64,
+    to_block: u64,
+    interval: u64,
+) -> Vec<BalanceSnapshot> {
+    let mut deltas = deltas.iter().peekable();
+    let mut balance = 0;
+
+    (from_block..to_block)
+        .step_by(interval.try_into().unwrap_or(usize::MAX))
+        .map(|block| {
+            let block_height = block as i64;
+            while let Some((_, delta)) = deltas.next_if(|(&height, _)| height <= block_height) {
+                balance += delta;
+            }
+            BalanceSnapshot {
+                block_height,
+                balance: balance.into(),
+            }
+        })
+        .collect()
+}
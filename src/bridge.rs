@@ -0,0 +1,90 @@
+use napi::bindgen_prelude::BigInt;
+
+use crate::response::QueryResponseDataTyped;
+
+const MESSAGE_OUT: u8 = 10;
+const INPUT_MESSAGE: u8 = 2;
+/// Length of a nonce in hex digits
+const NONCE_HEX_LEN: usize = 64;
+
+/// A message sent between the base layer and Fuel.
+///
+/// Deposits and withdrawals are reconciled by their nonce.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BridgeMessage {
+    /// "withdrawal" for MessageOut receipts sent to the base layer, "deposit" for
+    /// InputMessage inputs that consume a message sent from the base layer
+    pub direction: String,
+    /// Nonce of the message as 32 bytes of hex
+    pub nonce: String,
+    /// The address of the message sender.
+    pub sender: Option<String>,
+    /// The address of the message recipient.
+    pub recipient: Option<String>,
+    /// Amount of base asset sent with the message
+    pub amount: Option<BigInt>,
+    /// Hash of the message data, only set for withdrawals
+    pub digest: Option<String>,
+    /// The message data.
+    pub data: Option<String>,
+    pub tx_id: String,
+    pub block_height: i64,
+    /// Index of the MessageOut receipt, only set for withdrawals
+    pub receipt_index: Option<i64>,
+}
+
+/// Extract the bridge messages from the response data, ordered by block.
+///
+/// Messages without a nonce are left out since they can't be reconciled.
+pub fn extract_messages(data: QueryResponseDataTyped) -> Vec<BridgeMessage> {
+    let withdrawals = data
+        .receipts
+        .into_iter()
+        .filter(|receipt| receipt.receipt_type == MESSAGE_OUT)
+        .filter_map(|receipt| {
+            Some(BridgeMessage {
+                direction: "withdrawal".into(),
+                nonce: pad_nonce(&receipt.nonce?),
+                sender: receipt.sender,
+                recipient: receipt.recipient,
+                amount: receipt.amount,
+                digest: receipt.digest,
+                data: receipt.data,
+                tx_id: receipt.tx_id,
+                block_height: receipt.block_height,
+                receipt_index: Some(receipt.receipt_index),
+            })
+        });
+
+    let deposits = data
+        .inputs
+        .into_iter()
+        .filter(|input| input.input_type == INPUT_MESSAGE)
+        .filter_map(|input| {
+            Some(BridgeMessage {
+                direction: "deposit".into(),
+                nonce: input.nonce?,
+                sender: input.sender,
+                recipient: input.recipient,
+                amount: input.amount,
+                digest: None,
+                data: input.data,
+                tx_id: input.tx_id,
+                block_height: input.block_height,
+                receipt_index: None,
+            })
+        });
+
+    let mut messages: Vec<BridgeMessage> = withdrawals.chain(deposits).collect();
+    messages.sort_by_key(|message| message.block_height);
+
+    messages
+}
+
+/// The nonce of a MessageOut receipt is a quantity without leading zeros, pad it to the
+/// 32 bytes of the nonce of the InputMessage that consumes the message.
+fn pad_nonce(nonce: &str) -> String {
+    let digits = nonce.strip_prefix("0x").unwrap_or(nonce);
+    format!("0x{:0>width$}", digits, width = NONCE_HEX_LEN)
+}
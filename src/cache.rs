@@ -0,0 +1,213 @@
+use std::{collections::HashMap, fs, io::ErrorKind, path::PathBuf, sync::Mutex, time::SystemTime};
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use hyperfuel_net_types::Query;
+use serde_json::{json, Value};
+use xxhash_rust::xxh3::xxh3_128;
+
+use crate::config::Config;
+
+const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+const DEFAULT_FINALITY_MARGIN_BLOCKS: u64 = 100;
+const FILE_EXTENSION: &str = "bin";
+
+/// Caches raw query responses on disk.
+///
+/// Entries are keyed by the endpoints of the client and the normalized query and evicted
+/// least recently used first once the total size of the cache goes over the limit.
+pub struct ResponseCache {
+    dir: PathBuf,
+    /// Sorted urls of the endpoints the client sends requests to
+    urls: Vec<String>,
+    max_bytes: u64,
+    finality_margin_blocks: u64,
+    index: Mutex<Index>,
+}
+
+#[derive(Default)]
+struct Index {
+    entries: HashMap<u128, Entry>,
+    total_bytes: u64,
+}
+
+struct Entry {
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl ResponseCache {
+    /// Open the cache configured in `cfg`, returns `None` if caching is not enabled.
+    pub fn from_config(cfg: &Config) -> Result<Option<Self>> {
+        let Some(dir) = &cfg.cache_dir else {
+            return Ok(None);
+        };
+
+        let max_bytes = match cfg.cache_max_bytes {
+            Some(max) => max.try_into().context("cache_max_bytes is negative")?,
+            None => DEFAULT_MAX_BYTES,
+        };
+        let finality_margin_blocks = match cfg.cache_finality_margin_blocks {
+            Some(margin) => margin
+                .try_into()
+                .context("cache_finality_margin_blocks is negative")?,
+            None => DEFAULT_FINALITY_MARGIN_BLOCKS,
+        };
+
+        let mut urls: Vec<String> = std::iter::once(&cfg.url)
+            .chain(cfg.endpoints.iter().flatten().map(|endpoint| &endpoint.url))
+            .map(|url| url.trim_end_matches('/').to_owned())
+            .collect();
+        urls.sort();
+        urls.dedup();
+
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir).context("create cache directory")?;
+        let index = load_index(&dir).context("read cache directory")?;
+
+        Ok(Some(Self {
+            dir,
+            urls,
+            max_bytes,
+            finality_margin_blocks,
+            index: Mutex::new(index),
+        }))
+    }
+
+    /// Whether a page that ends at `next_block` is final enough to be cached.
+    pub fn is_cacheable(&self, next_block: u64, archive_height: Option<u64>) -> bool {
+        archive_height
+            .is_some_and(|height| next_block.saturating_add(self.finality_margin_blocks) <= height)
+    }
+
+    /// Get the cached response of a query.
+    pub fn get(&self, key: u128) -> Result<Option<Bytes>> {
+        let mut index = self.index.lock().unwrap();
+        let Some(entry) = index.entries.get_mut(&key) else {
+            return Ok(None);
+        };
+
+        let path = self.path(key);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                entry.last_used = SystemTime::now();
+                // persist the access time so the eviction order survives restarts
+                if let Ok(file) = fs::File::options().write(true).open(&path) {
+                    file.set_modified(entry.last_used).ok();
+                }
+                Ok(Some(bytes.into()))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                let size = entry.size;
+                index.entries.remove(&key);
+                index.total_bytes -= size;
+                Ok(None)
+            }
+            Err(e) => Err(e).context("read cache entry"),
+        }
+    }
+
+    /// Store the response of a query, evicting least recently used entries to make room.
+    pub fn put(&self, key: u128, bytes: &[u8]) -> Result<()> {
+        let size = bytes.len() as u64;
+        if size > self.max_bytes {
+            return Ok(());
+        }
+
+        let path = self.path(key);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes).context("write cache entry")?;
+        fs::rename(&tmp_path, &path).context("move cache entry into place")?;
+
+        let mut index = self.index.lock().unwrap();
+        let entry = Entry {
+            size,
+            last_used: SystemTime::now(),
+        };
+        if let Some(old) = index.entries.insert(key, entry) {
+            index.total_bytes -= old.size;
+        }
+        index.total_bytes += size;
+
+        while index.total_bytes > self.max_bytes {
+            let oldest = index
+                .entries
+                .iter()
+                .filter(|(&k, _)| k != key)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(&k, _)| k);
+            let Some(oldest) = oldest else {
+                break;
+            };
+            let entry = index.entries.remove(&oldest).unwrap();
+            index.total_bytes -= entry.size;
+            match fs::remove_file(self.path(oldest)) {
+                Ok(()) => (),
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e).context("evict cache entry"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cache key of a query.
+    ///
+    /// The urls of the endpoints are part of the key so clients of different instances
+    /// don't share entries. Selections and their values are matched as sets by the server,
+    /// so arrays are sorted and deduplicated before hashing to make equivalent queries share
+    /// an entry.
+    pub fn key(&self, query: &Query) -> Result<u128> {
+        let mut value = serde_json::to_value(query).context("serialize query")?;
+        normalize(&mut value);
+        let json = serde_json::to_vec(&json!({ "urls": self.urls, "query": value }))
+            .context("serialize normalized query")?;
+
+        Ok(xxh3_128(&json))
+    }
+
+    fn path(&self, key: u128) -> PathBuf {
+        self.dir.join(format!("{:032x}.{}", key, FILE_EXTENSION))
+    }
+}
+
+fn normalize(value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            items.iter_mut().for_each(normalize);
+            items.sort_by_cached_key(|item| item.to_string());
+            items.dedup();
+        }
+        Value::Object(fields) => fields.values_mut().for_each(normalize),
+        _ => (),
+    }
+}
+
+fn load_index(dir: &PathBuf) -> Result<Index> {
+    let mut index = Index::default();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(FILE_EXTENSION) {
+            continue;
+        }
+        let key = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| u128::from_str_radix(stem, 16).ok())
+            .ok_or_else(|| anyhow!("unexpected file in cache directory: {}", path.display()))?;
+        let metadata = entry.metadata()?;
+
+        index.total_bytes += metadata.len();
+        index.entries.insert(
+            key,
+            Entry {
+                size: metadata.len(),
+                last_used: metadata.modified()?,
+            },
+        );
+    }
+
+    Ok(index)
+}
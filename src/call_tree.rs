@@ -0,0 +1,184 @@
+use napi::bindgen_prelude::BigInt;
+
+use crate::types::Receipt;
+
+const CALL: u8 = 0;
+const RETURN: u8 = 1;
+const RETURN_DATA: u8 = 2;
+const PANIC: u8 = 3;
+const REVERT: u8 = 4;
+const LOG: u8 = 5;
+const LOG_DATA: u8 = 6;
+const TRANSFER: u8 = 7;
+const TRANSFER_OUT: u8 = 8;
+const SCRIPT_RESULT: u8 = 9;
+
+/// Execution tree of a single transaction.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CallTree {
+    pub tx_id: String,
+    pub block_height: i64,
+    /// Execution of the script, the contract calls made by the script are its children
+    pub root: CallNode,
+    /// ScriptResult receipt of the transaction if it was among the receipts
+    pub script_result: Option<Receipt>,
+}
+
+/// A call frame in the execution of a transaction.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CallNode {
+    /// Contract that was called, not set for the script
+    pub contract_id: Option<String>,
+    /// Contract that made the call, not set if the script made it
+    pub caller: Option<String>,
+    /// Index of the Call receipt that started this frame
+    pub receipt_index: Option<i64>,
+    /// Amount of coins forwarded with the call
+    pub amount: Option<BigInt>,
+    /// Asset id of the coins forwarded with the call
+    pub asset_id: Option<String>,
+    /// Gas forwarded to the call
+    pub gas: Option<i64>,
+    /// Function selector of the call
+    pub param1: Option<BigInt>,
+    /// Argument of the call
+    pub param2: Option<BigInt>,
+    /// "success" if the frame returned, "revert" or "panic" if it failed, "unfinished" if
+    /// it was unwound by a failure in a nested call or its receipts are missing
+    pub outcome: String,
+    /// Return or ReturnData receipt that ended the frame
+    pub return_value: Option<Receipt>,
+    /// Revert or Panic receipt that ended the frame
+    pub failure: Option<Receipt>,
+    /// Log and LogData receipts emitted in this frame
+    pub logs: Vec<Receipt>,
+    /// Transfer and TransferOut receipts emitted in this frame
+    pub transfers: Vec<Receipt>,
+    /// Other receipts emitted in this frame, e.g. Mint, Burn or MessageOut
+    pub receipts: Vec<Receipt>,
+    /// Calls made from this frame in order
+    pub calls: Vec<CallNode>,
+}
+
+impl CallNode {
+    fn script() -> Self {
+        Self::new(None, None)
+    }
+
+    fn new(contract_id: Option<String>, caller: Option<String>) -> Self {
+        Self {
+            contract_id,
+            caller,
+            receipt_index: None,
+            amount: None,
+            asset_id: None,
+            gas: None,
+            param1: None,
+            param2: None,
+            outcome: "unfinished".into(),
+            return_value: None,
+            failure: None,
+            logs: Vec::new(),
+            transfers: Vec::new(),
+            receipts: Vec::new(),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Frame of a Call receipt made from the frame of `caller`, which is `None` for the
+    /// script. The Call receipts of the script have the zero contract id as `contract_id`.
+    fn call(receipt: Receipt, caller: Option<String>) -> Self {
+        Self {
+            receipt_index: Some(receipt.receipt_index),
+            amount: receipt.amount,
+            asset_id: receipt.asset_id,
+            gas: receipt.gas,
+            param1: receipt.param1,
+            param2: receipt.param2,
+            ..Self::new(receipt.to, caller)
+        }
+    }
+}
+
+/// Rebuild the call tree of every transaction from its receipts.
+///
+/// Trees are returned in the order their transactions first appear in `receipts`.
+/// The receipts of a transaction have to be complete for the tree to be accurate, so the
+/// query shouldn't filter receipts by type or contract.
+pub fn build_call_trees(receipts: Vec<Receipt>) -> Vec<CallTree> {
+    let mut txs: Vec<(String, i64, Vec<Receipt>)> = Vec::new();
+    for receipt in receipts {
+        let pos = txs.iter().rposition(|(tx_id, height, _)| {
+            *tx_id == receipt.tx_id && *height == receipt.block_height
+        });
+        match pos {
+            Some(pos) => txs[pos].2.push(receipt),
+            None => txs.push((receipt.tx_id.clone(), receipt.block_height, vec![receipt])),
+        }
+    }
+
+    txs.into_iter()
+        .map(|(tx_id, block_height, mut receipts)| {
+            receipts.sort_by_key(|receipt| receipt.receipt_index);
+            build_call_tree(tx_id, block_height, receipts)
+        })
+        .collect()
+}
+
+fn build_call_tree(tx_id: String, block_height: i64, receipts: Vec<Receipt>) -> CallTree {
+    let mut stack = vec![CallNode::script()];
+    let mut script_result = None;
+
+    for receipt in receipts {
+        // the stack always holds the script frame at the bottom
+        let top = stack.last_mut().unwrap();
+        match receipt.receipt_type {
+            CALL => {
+                let caller = top.contract_id.clone();
+                stack.push(CallNode::call(receipt, caller));
+            }
+            RETURN | RETURN_DATA => {
+                top.outcome = "success".into();
+                top.return_value = Some(receipt);
+                finish_frame(&mut stack);
+            }
+            REVERT | PANIC => {
+                top.outcome = if receipt.receipt_type == REVERT {
+                    "revert".into()
+                } else {
+                    "panic".into()
+                };
+                top.failure = Some(receipt);
+                // a failure aborts the whole transaction, nothing returns to the callers
+                while stack.len() > 1 {
+                    finish_frame(&mut stack);
+                }
+            }
+            LOG | LOG_DATA => top.logs.push(receipt),
+            TRANSFER | TRANSFER_OUT => top.transfers.push(receipt),
+            SCRIPT_RESULT => script_result = Some(receipt),
+            _ => top.receipts.push(receipt),
+        }
+    }
+
+    while stack.len() > 1 {
+        finish_frame(&mut stack);
+    }
+
+    CallTree {
+        tx_id,
+        block_height,
+        root: stack.pop().unwrap(),
+        script_result,
+    }
+}
+
+/// Pop the top frame and attach it to its caller, the script frame is never popped.
+fn finish_frame(stack: &mut Vec<CallNode>) {
+    if stack.len() > 1 {
+        let frame = stack.pop().unwrap();
+        stack.last_mut().unwrap().calls.push(frame);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::{Context, Result};
 use serde::Serialize;
 
@@ -9,9 +11,168 @@ pub struct Config {
     /// Optional bearer_token to put into http requests made to source hypersync instance
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bearer_token: Option<String>,
+    /// Name of an environment variable to read the bearer token from before every request
+    #[serde(skip)]
+    pub bearer_token_env: Option<String>,
+    /// Path of a file to read the bearer token from, the file is read again when it changes
+    #[serde(skip)]
+    pub bearer_token_file: Option<String>,
     /// Timout treshold for a single http request in milliseconds, default is 30 seconds (30_000ms)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http_req_timeout_millis: Option<i64>,
+    /// Label to register the statistics of this client under in the process wide metrics registry.
+    /// Statistics are only collected if this is set, see `renderMetrics`.
+    /// Clients that use the same label share their statistics.
+    #[serde(skip)]
+    pub metrics_label: Option<String>,
+    /// Static headers to add to every http request made to the source hypersync instance
+    #[serde(skip)]
+    pub http_headers: Option<HashMap<String, String>>,
+    /// Appended to the user agent of the client, e.g. the name and version of the application
+    #[serde(skip)]
+    pub user_agent_suffix: Option<String>,
+    /// Proxies to send the http requests through.
+    /// Proxy settings from the environment are ignored if this is set.
+    #[serde(skip)]
+    pub proxy: Option<ProxyConfig>,
+    /// Custom certificate authorities and client certificate for mTLS
+    #[serde(skip)]
+    pub tls: Option<TlsConfig>,
+    /// Additional instances to fail over to when a request fails with a retryable error.
+    /// `url` is used as an endpoint with priority 0 and weight 1.
+    #[serde(skip)]
+    pub endpoints: Option<Vec<EndpointConfig>>,
+    /// Endpoints whose reported height is more than this many blocks behind the highest height
+    /// reported by any endpoint are only used if no other endpoint is available.
+    #[serde(skip)]
+    pub endpoint_max_lag_blocks: Option<i64>,
+    /// Maximum number of requests to send per second, unlimited by default
+    #[serde(skip)]
+    pub max_requests_per_second: Option<f64>,
+    /// Number of requests that can be sent at once before `maxRequestsPerSecond` kicks in,
+    /// default is 1
+    #[serde(skip)]
+    pub request_burst: Option<i64>,
+    /// Maximum number of requests that can be in flight at the same time, unlimited by default
+    #[serde(skip)]
+    pub max_concurrent_requests: Option<i64>,
+    /// Clients with the same rate limit group share their rate limit and concurrency cap.
+    /// The limits of the first client created in a group apply to the whole group.
+    #[serde(skip)]
+    pub rate_limit_group: Option<String>,
+    /// Number of times to retry a request that every endpoint rejected with HTTP 429 or 503
+    /// before failing with a RateLimited error, default is 3.
+    /// Requests are not retried if the server asks to wait for more than a minute.
+    #[serde(skip)]
+    pub rate_limit_retries: Option<i64>,
+    /// Directory to cache query responses in. Caching is disabled if this is not set.
+    #[serde(skip)]
+    pub cache_dir: Option<String>,
+    /// Maximum total size of the cached responses in bytes, default is 1GiB.
+    /// Least recently used responses are evicted first.
+    #[serde(skip)]
+    pub cache_max_bytes: Option<i64>,
+    /// Responses are only cached if they end at least this many blocks below the archive height,
+    /// default is 100
+    #[serde(skip)]
+    pub cache_finality_margin_blocks: Option<i64>,
+    /// "record" to save every request and response to `fixturesDir`, "replay" to serve the
+    /// saved responses in order without touching the network.
+    /// Replaying fails with a diff if a request doesn't match the recorded one.
+    #[serde(skip)]
+    pub fixtures_mode: Option<String>,
+    /// Directory of the recorded fixtures. Existing fixtures are removed when recording.
+    #[serde(skip)]
+    pub fixtures_dir: Option<String>,
+    /// Number of rows that each page of a paginated operation like `createParquetFolder` should
+    /// have. The block range and `maxNumBlocks`/`maxNumTransactions` of the requests are adapted
+    /// to the density of the pages received so far.
+    #[serde(skip)]
+    pub target_page_rows: Option<i64>,
+    /// Size in bytes that the response of each page of a paginated operation should have,
+    /// pages are sized to meet both targets if `targetPageRows` is also set.
+    #[serde(skip)]
+    pub target_page_bytes: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct EndpointConfig {
+    /// Url of the hypersync instance
+    pub url: String,
+    /// Bearer token to use for this instance instead of the one in the client config
+    pub bearer_token: Option<String>,
+    /// Endpoints with a lower priority are preferred, default is 0
+    pub priority: Option<i64>,
+    /// Relative share of the requests that this endpoint gets among the healthy
+    /// endpoints of the same priority, default is 1
+    pub weight: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct ProxyConfig {
+    /// Proxy url to use for http requests
+    pub http: Option<String>,
+    /// Proxy url to use for https requests
+    pub https: Option<String>,
+    /// Proxy url to use for all requests.
+    /// Supports socks5:// and socks5h:// urls in addition to http(s) proxies.
+    pub all: Option<String>,
+    /// Hosts, domains or ip ranges that should be reached without going through the proxy,
+    /// e.g. "localhost", ".internal.example.com" or "10.0.0.0/8"
+    pub no_proxy: Option<Vec<String>>,
+}
+
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct TlsConfig {
+    /// Path to a PEM file with certificate authorities to trust in addition to the built in ones
+    pub ca_bundle_path: Option<String>,
+    /// Path to a PEM file with the client certificate chain to present for mTLS
+    pub client_cert_path: Option<String>,
+    /// Path to a PEM file with the private key of the client certificate
+    pub client_key_path: Option<String>,
+}
+
+#[cfg(feature = "fault-injection")]
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct FaultInjectionConfig {
+    /// Seed of the random number generator to make the injected faults reproducible
+    pub seed: Option<i64>,
+    /// Probability of a request failing with a connection reset
+    pub connection_reset_probability: Option<f64>,
+    /// Probability of a request timing out after `httpReqTimeoutMillis`
+    pub timeout_probability: Option<f64>,
+    /// Probability of a request failing with HTTP 500
+    pub server_error_probability: Option<f64>,
+    /// Probability of a request failing with HTTP 429
+    pub rate_limit_probability: Option<f64>,
+    /// Probability of a response body being cut off at a random length
+    pub truncated_body_probability: Option<f64>,
+    /// Probability of random bytes of a response body being changed
+    pub corrupted_body_probability: Option<f64>,
+    /// Probability of a response arriving after `slowResponseDelayMillis`
+    pub slow_response_probability: Option<f64>,
+    /// Extra delay of slow responses in milliseconds, default is 1000
+    pub slow_response_delay_millis: Option<i64>,
+    /// Retry-After of the injected HTTP 429 responses in milliseconds, not sent by default
+    pub rate_limit_retry_after_millis: Option<i64>,
+    /// Faults to inject into specific requests regardless of the probabilities
+    pub schedule: Option<Vec<ScheduledFault>>,
+}
+
+#[cfg(feature = "fault-injection")]
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct ScheduledFault {
+    /// Index of the request to fail, counting from 0.
+    /// Every request sent over the network counts, including retries and failovers.
+    pub request: i64,
+    /// One of "connection_reset", "timeout", "server_error", "rate_limit", "truncated_body",
+    /// "corrupted_body" or "slow_response"
+    pub fault: String,
 }
 
 impl Config {
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use hyperfuel_client::{ArrowIpc, QueryResponse, QueryResponseData, TransportFormat};
+use hyperfuel_net_types::hyperfuel_net_types_capnp;
+
+/// Parse a packed capnp query response body into arrow data.
+///
+/// Returns the response along with the size of the unpacked message in bytes.
+pub fn parse_query_response(bytes: &[u8]) -> Result<(QueryResponse, usize)> {
+    let mut opts = capnp::message::ReaderOptions::new();
+    opts.nesting_limit(i32::MAX).traversal_limit_in_words(None);
+    let segments = capnp::serialize_packed::read_message(bytes, opts)
+        .context("create message reader")?
+        .into_segments();
+    let unpacked_size = segments.len();
+    let message_reader = capnp::message::Reader::new(segments, opts);
+
+    let query_response = message_reader
+        .get_root::<hyperfuel_net_types_capnp::query_response::Reader>()
+        .context("get root")?;
+
+    let archive_height = match query_response.get_archive_height() {
+        -1 => None,
+        h => Some(
+            h.try_into()
+                .context("invalid archive height returned from server")?,
+        ),
+    };
+
+    let data = query_response.get_data().context("read data")?;
+
+    let blocks = ArrowIpc::read_chunks(data.get_blocks().context("get data")?)
+        .context("parse block data")?;
+    let transactions = ArrowIpc::read_chunks(data.get_transactions().context("get data")?)
+        .context("parse tx data")?;
+    let receipts = ArrowIpc::read_chunks(data.get_receipts().context("get data")?)
+        .context("parse receipt data")?;
+    let inputs = ArrowIpc::read_chunks(data.get_inputs().context("get data")?)
+        .context("parse input data")?;
+    let outputs = ArrowIpc::read_chunks(data.get_outputs().context("get data")?)
+        .context("parse output data")?;
+
+    let res = QueryResponse {
+        archive_height,
+        next_block: query_response.get_next_block(),
+        total_execution_time: query_response.get_total_execution_time(),
+        data: QueryResponseData {
+            blocks,
+            transactions,
+            receipts,
+            inputs,
+            outputs,
+        },
+    };
+
+    Ok((res, unpacked_size))
+}
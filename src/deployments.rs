@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::{response::QueryResponseDataTyped, types::Transaction};
+
+const OUTPUT_CONTRACT_CREATED: u8 = 4;
+
+/// Options of `presetQueryGetContractDeployments`.
+#[napi(object)]
+#[derive(Default, Debug, Clone)]
+pub struct ContractDeploymentFilter {
+    /// Only return deployments of these contracts
+    pub contract_ids: Option<Vec<String>>,
+    /// Return the bytecode of the deployed contracts, default is false
+    pub include_bytecode: Option<bool>,
+}
+
+/// A contract that was deployed by a Create transaction.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ContractDeployment {
+    pub contract_id: String,
+    /// Initial state root of the contract
+    pub state_root: Option<String>,
+    /// Root of the contract bytecode
+    pub bytecode_root: Option<String>,
+    /// Salt that was used to derive the contract id
+    pub salt: Option<String>,
+    /// Create transaction that deployed the contract
+    pub tx_id: String,
+    pub block_height: i64,
+    /// Time the deploying transaction was executed
+    pub time: Option<i64>,
+    /// Bytecode of the contract if it was requested, read from the witness at the
+    /// `bytecode_witness_index` of the transaction
+    pub bytecode: Option<String>,
+    /// Set if the bytecode was requested but couldn't be read. The server only returns the
+    /// first witness of a transaction, so the bytecode is only available if its witness
+    /// index is 0.
+    pub bytecode_unavailable: bool,
+}
+
+/// Pair every ContractCreated output with its deploying transaction.
+pub fn extract_deployments(
+    data: QueryResponseDataTyped,
+    include_bytecode: bool,
+) -> Vec<ContractDeployment> {
+    let txs: HashMap<&str, _> = data
+        .transactions
+        .iter()
+        .map(|tx| (tx.id.as_str(), tx))
+        .collect();
+
+    data.outputs
+        .iter()
+        .filter(|output| output.output_type == OUTPUT_CONTRACT_CREATED)
+        .filter_map(|output| {
+            let contract_id = output.contract.clone()?;
+            let tx = txs.get(output.tx_id.as_str());
+            let bytecode = tx
+                .filter(|_| include_bytecode)
+                .and_then(|tx| witness(tx, tx.bytecode_witness_index?));
+
+            Some(ContractDeployment {
+                contract_id,
+                state_root: output.state_root.clone(),
+                bytecode_root: tx.and_then(|tx| tx.bytecode_root.clone()),
+                salt: tx.and_then(|tx| tx.salt.clone()),
+                tx_id: output.tx_id.clone(),
+                block_height: output.block_height,
+                time: tx.map(|tx| tx.time),
+                bytecode_unavailable: include_bytecode && bytecode.is_none(),
+                bytecode,
+            })
+        })
+        .collect()
+}
+
+/// Witness of the transaction at `index`, if the server returned it.
+///
+/// The witnesses column of the hyperfuel schema only holds the first witness of a
+/// transaction.
+fn witness(tx: &Transaction, index: i64) -> Option<String> {
+    match index {
+        0 => tx.witnesses.clone(),
+        _ => None,
+    }
+}
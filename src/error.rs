@@ -0,0 +1,73 @@
+use napi::{bindgen_prelude::ToNapiValue, sys, Env, JsObject, Status};
+
+use crate::{auth, transport::RateLimitedError};
+
+/// Details of an error that was caused by the server rate limiting the client.
+///
+/// Errors thrown because of rate limiting have the code `RateLimited` and these properties.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct RateLimited {
+    /// Http status code of the last response, 429 or 503
+    pub status: u32,
+    /// Wait suggested by the server in milliseconds, not set if the server didn't send
+    /// a `Retry-After` header
+    pub retry_after_ms: Option<i64>,
+}
+
+impl RateLimited {
+    /// Rate limit details of the error, if it was caused by rate limiting.
+    pub fn from_error(err: &anyhow::Error) -> Option<Self> {
+        let e = err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<RateLimitedError>())?;
+        Some(Self {
+            status: e.status.as_u16().into(),
+            retry_after_ms: e.retry_after.map(|d| d.as_millis() as i64),
+        })
+    }
+}
+
+/// Convert an error into a javascript error.
+///
+/// Bearer tokens are redacted from the message.
+pub fn to_napi(err: anyhow::Error) -> napi::Error {
+    napi::Error::new(Status::GenericFailure, auth::redact(&format!("{:?}", err)))
+}
+
+/// Result of an async operation that is converted into javascript once it finished.
+///
+/// Errors are thrown while converting, because the typed properties of rate limited errors
+/// can only be set on the javascript thread. Errors caused by rate limiting have the code
+/// `RateLimited` and the `status` and `retryAfterMs` properties of `RateLimited`.
+pub struct Outcome<T>(pub anyhow::Result<T>);
+
+impl<T: ToNapiValue> ToNapiValue for Outcome<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        let err = match val.0 {
+            Ok(value) => return T::to_napi_value(env, value),
+            Err(err) => err,
+        };
+        let rate_limited = RateLimited::from_error(&err);
+        let err = to_napi(err);
+        let Some(rate_limited) = rate_limited else {
+            return Err(err);
+        };
+
+        let env = Env::from_raw(env);
+        match rate_limited_error(&env, err.reason.clone(), &rate_limited) {
+            Ok(js_err) => Err(napi::Error::from(js_err.into_unknown())),
+            Err(_) => Err(err),
+        }
+    }
+}
+
+fn rate_limited_error(env: &Env, reason: String, details: &RateLimited) -> napi::Result<JsObject> {
+    let mut js_err = env.create_error(napi::Error::new(Status::GenericFailure, reason))?;
+    js_err.set_named_property("code", env.create_string("RateLimited")?)?;
+    js_err.set_named_property("status", details.status)?;
+    if let Some(retry_after_ms) = details.retry_after_ms {
+        js_err.set_named_property("retryAfterMs", retry_after_ms)?;
+    }
+    Ok(js_err)
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashMap, fmt, sync::Mutex, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reqwest::StatusCode;
+
+use crate::{config::FaultInjectionConfig, transport::StatusError};
+
+/// Failure that can be injected into a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The connection is reset before a response arrives
+    ConnectionReset,
+    /// The request times out after the configured request timeout
+    Timeout,
+    /// The server answers with HTTP 500
+    ServerError,
+    /// The server answers with HTTP 429
+    RateLimit,
+    /// The response body ends early
+    TruncatedBody,
+    /// Random bytes of the response body are changed
+    CorruptedBody,
+    /// The response arrives after an extra delay
+    SlowResponse,
+}
+
+const FAULTS: [Fault; 7] = [
+    Fault::ConnectionReset,
+    Fault::Timeout,
+    Fault::ServerError,
+    Fault::RateLimit,
+    Fault::TruncatedBody,
+    Fault::CorruptedBody,
+    Fault::SlowResponse,
+];
+
+impl Fault {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ConnectionReset => "connection_reset",
+            Self::Timeout => "timeout",
+            Self::ServerError => "server_error",
+            Self::RateLimit => "rate_limit",
+            Self::TruncatedBody => "truncated_body",
+            Self::CorruptedBody => "corrupted_body",
+            Self::SlowResponse => "slow_response",
+        }
+    }
+
+    fn parse(name: &str) -> Result<Self> {
+        FAULTS
+            .into_iter()
+            .find(|f| f.name() == name)
+            .ok_or_else(|| anyhow!("unknown fault {}", name))
+    }
+}
+
+/// Error of a request that failed because of an injected fault.
+#[derive(Debug)]
+pub struct InjectedFault(pub Fault);
+
+impl fmt::Display for InjectedFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "injected fault: {}", self.0.name())
+    }
+}
+
+impl std::error::Error for InjectedFault {}
+
+/// Decides which requests fail and how, based on the fault injection config.
+///
+/// Every request that reaches the http layer counts, including retries and failovers.
+pub struct FaultInjector {
+    probabilities: Vec<(Fault, f64)>,
+    schedule: HashMap<u64, Fault>,
+    slow_response_delay: Duration,
+    rate_limit_retry_after: Option<Duration>,
+    timeout: Duration,
+    state: Mutex<State>,
+}
+
+struct State {
+    rng: StdRng,
+    requests: u64,
+}
+
+impl FaultInjector {
+    /// Build the injector configured in `faults`.
+    ///
+    /// `timeout` is the request timeout, injected timeouts fail after this long.
+    pub fn new(faults: &FaultInjectionConfig, timeout: Duration) -> Result<Self> {
+        let probabilities = [
+            (Fault::ConnectionReset, faults.connection_reset_probability),
+            (Fault::Timeout, faults.timeout_probability),
+            (Fault::ServerError, faults.server_error_probability),
+            (Fault::RateLimit, faults.rate_limit_probability),
+            (Fault::TruncatedBody, faults.truncated_body_probability),
+            (Fault::CorruptedBody, faults.corrupted_body_probability),
+            (Fault::SlowResponse, faults.slow_response_probability),
+        ]
+        .into_iter()
+        .filter_map(|(fault, p)| p.map(|p| (fault, p)))
+        .collect::<Vec<_>>();
+        for &(fault, p) in probabilities.iter() {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(anyhow!(
+                    "probability of {} has to be between 0 and 1",
+                    fault.name()
+                ));
+            }
+        }
+        if probabilities.iter().map(|(_, p)| p).sum::<f64>() > 1.0 {
+            return Err(anyhow!("fault probabilities add up to more than 1"));
+        }
+
+        let mut schedule = HashMap::new();
+        for scheduled in faults.schedule.iter().flatten() {
+            let request = scheduled
+                .request
+                .try_into()
+                .context("scheduled fault request is negative")?;
+            schedule.insert(request, Fault::parse(&scheduled.fault)?);
+        }
+
+        let slow_response_delay = faults
+            .slow_response_delay_millis
+            .unwrap_or(1000)
+            .try_into()
+            .map(Duration::from_millis)
+            .context("slow_response_delay_millis is negative")?;
+        let rate_limit_retry_after = faults
+            .rate_limit_retry_after_millis
+            .map(|ms| ms.try_into().map(Duration::from_millis))
+            .transpose()
+            .context("rate_limit_retry_after_millis is negative")?;
+
+        let rng = match faults.seed {
+            Some(seed) => StdRng::seed_from_u64(seed as u64),
+            None => StdRng::from_entropy(),
+        };
+
+        Ok(Self {
+            probabilities,
+            schedule,
+            slow_response_delay,
+            rate_limit_retry_after,
+            timeout,
+            state: Mutex::new(State { rng, requests: 0 }),
+        })
+    }
+
+    /// Pick the fault to inject into the next request, if any.
+    ///
+    /// Scheduled faults take precedence, otherwise at most one fault is picked by probability.
+    pub fn next(&self) -> Option<Fault> {
+        let mut state = self.state.lock().unwrap();
+        let request = state.requests;
+        state.requests += 1;
+
+        if let Some(&fault) = self.schedule.get(&request) {
+            return Some(fault);
+        }
+
+        let mut roll: f64 = state.rng.gen();
+        for &(fault, p) in self.probabilities.iter() {
+            if roll < p {
+                return Some(fault);
+            }
+            roll -= p;
+        }
+
+        None
+    }
+
+    /// Fail the request the way the fault says, without sending it.
+    ///
+    /// Returns `Ok` for faults that change the response instead.
+    pub async fn fail_request(&self, fault: Fault) -> Result<()> {
+        match fault {
+            Fault::ConnectionReset => Err(InjectedFault(fault).into()),
+            Fault::Timeout => {
+                tokio::time::sleep(self.timeout).await;
+                Err(InjectedFault(fault).into())
+            }
+            Fault::ServerError => Err(StatusError {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                body: InjectedFault(fault).to_string(),
+                retry_after: None,
+            }
+            .into()),
+            Fault::RateLimit => Err(StatusError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                body: InjectedFault(fault).to_string(),
+                retry_after: self.rate_limit_retry_after,
+            }
+            .into()),
+            Fault::TruncatedBody | Fault::CorruptedBody | Fault::SlowResponse => Ok(()),
+        }
+    }
+
+    /// Delay the response if the fault says so.
+    pub async fn delay_response(&self, fault: Fault) {
+        if fault == Fault::SlowResponse {
+            tokio::time::sleep(self.slow_response_delay).await;
+        }
+    }
+
+    /// Damage the response body if the fault says so.
+    pub fn damage_body(&self, fault: Fault, bytes: Bytes) -> Bytes {
+        if bytes.is_empty() {
+            return bytes;
+        }
+        let rng = &mut self.state.lock().unwrap().rng;
+        match fault {
+            Fault::TruncatedBody => bytes.slice(..rng.gen_range(0..bytes.len())),
+            Fault::CorruptedBody => {
+                let mut body = bytes.to_vec();
+                for _ in 0..(body.len() / 100).max(1) {
+                    let pos = rng.gen_range(0..body.len());
+                    body[pos] ^= rng.gen_range(1..=u8::MAX);
+                }
+                Bytes::from(body)
+            }
+            _ => bytes,
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use napi::bindgen_prelude::BigInt;
+
+use crate::response::QueryResponseDataTyped;
+
+const CALL: u8 = 0;
+const TRANSFER: u8 = 7;
+const TRANSFER_OUT: u8 = 8;
+const SCRIPT_RESULT: u8 = 9;
+const MESSAGE_OUT: u8 = 10;
+const INPUT_COIN: u8 = 0;
+const INPUT_MESSAGE: u8 = 2;
+const OUTPUT_COIN: u8 = 0;
+const OUTPUT_CHANGE: u8 = 2;
+const OUTPUT_VARIABLE: u8 = 3;
+const TX_TYPE_MINT: u8 = 2;
+const ZERO_ASSET_ID: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Consensus parameters that are needed to turn gas into fees.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FeeParameters {
+    /// Divisor of the gas price, the fee of `gas` is `ceil(gas * gas_price / gas_price_factor)`
+    pub gas_price_factor: i64,
+    /// Asset the fees are paid in, the zero asset id if not set
+    pub base_asset_id: Option<String>,
+}
+
+/// Gas and fee of a transaction.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransactionFee {
+    pub tx_id: String,
+    pub block_height: i64,
+    /// The type of the transaction
+    pub tx_type: u8,
+    /// The status type of the transaction
+    pub status: u8,
+    /// Gas price of the block, not set if the mint transaction of the block is missing
+    pub gas_price: Option<i64>,
+    /// Gas used by the script, from the ScriptResult receipt
+    pub script_gas_used: Option<i64>,
+    /// Gas used by the predicates of the inputs
+    pub predicate_gas_used: i64,
+    /// Script and predicate gas
+    pub gas_used: i64,
+    pub tip: i64,
+    pub max_fee: Option<i64>,
+    /// Fee of the gas used, not set if the gas price is unknown
+    pub execution_fee: Option<BigInt>,
+    /// Lower bound of the fee: the execution fee plus tip, capped at the max fee of the
+    /// transaction. The fee for the size of the transaction is left out.
+    pub execution_fee_plus_tip: Option<BigInt>,
+    /// The fee paid, the base asset spent by the inputs that didn't end up in the outputs or
+    /// in contracts. Not set if the response has no inputs of the transaction.
+    pub fee: Option<BigInt>,
+}
+
+/// Gas and fees of a block.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BlockFees {
+    pub block_height: i64,
+    pub gas_price: Option<i64>,
+    /// Number of transactions in the block excluding the mint transaction
+    pub transaction_count: i64,
+    pub gas_used: i64,
+    pub tips: BigInt,
+    /// Sum of `execution_fee_plus_tip` of the transactions that have one, a lower bound of the fees
+    pub execution_fees_plus_tips: BigInt,
+    /// Sum of `fee` of the transactions that have one
+    pub fees: BigInt,
+    /// Amount of the mint transaction, the total the block producer collected
+    pub fees_collected: Option<BigInt>,
+}
+
+/// Fees of the transactions and blocks of a response.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FeeReport {
+    /// Transactions in the order of the response, mint transactions are skipped
+    pub transactions: Vec<TransactionFee>,
+    /// Blocks ordered by height
+    pub blocks: Vec<BlockFees>,
+}
+
+#[derive(Default)]
+struct BlockTotals {
+    gas_price: Option<i64>,
+    transaction_count: i64,
+    gas_used: i64,
+    tips: i128,
+    execution_fees_plus_tips: i128,
+    fees: i128,
+    fees_collected: Option<i128>,
+}
+
+/// Compute the gas used and the fee of every transaction in the response.
+///
+/// The fee is the base asset of the inputs minus the base asset of the coin, change and
+/// variable outputs. For transactions whose script succeeded, the base asset the script moved
+/// into contracts or to messages is subtracted and the base asset contracts transferred out is
+/// added back. This needs the inputs, outputs and receipts of the transactions, so the query
+/// shouldn't filter them.
+///
+/// The fee for the size of a transaction depends on the gas costs of the consensus parameters,
+/// which aren't part of the data, so the fee computed from the gas used is only a lower bound.
+/// The gas price of a block is taken from its mint transaction.
+pub fn compute_fees(data: QueryResponseDataTyped, params: &FeeParameters) -> Result<FeeReport> {
+    let factor = u128::try_from(params.gas_price_factor)
+        .ok()
+        .filter(|&factor| factor > 0)
+        .ok_or_else(|| anyhow!("gas_price_factor has to be positive"))?;
+
+    let mut blocks: BTreeMap<i64, BlockTotals> = BTreeMap::new();
+    for tx in data.transactions.iter() {
+        if tx.tx_type == TX_TYPE_MINT {
+            let block = blocks.entry(tx.block_height).or_default();
+            block.gas_price = tx.mint_gas_price;
+            block.fees_collected = tx.mint_amount.map(i128::from);
+        }
+    }
+
+    let script_gas: HashMap<&str, i64> = data
+        .receipts
+        .iter()
+        .filter(|receipt| receipt.receipt_type == SCRIPT_RESULT)
+        .filter_map(|receipt| Some((receipt.tx_id.as_str(), receipt.gas_used?)))
+        .collect();
+    let mut predicate_gas: HashMap<&str, i64> = HashMap::new();
+    for input in data.inputs.iter() {
+        *predicate_gas.entry(input.tx_id.as_str()).or_default() +=
+            input.predicate_gas_used.unwrap_or_default();
+    }
+
+    let base_asset_id = params.base_asset_id.as_deref().unwrap_or(ZERO_ASSET_ID);
+    let is_base_asset = |asset_id: &Option<String>| {
+        asset_id
+            .as_deref()
+            .is_some_and(|asset_id| asset_id.eq_ignore_ascii_case(base_asset_id))
+    };
+
+    // base asset that left the transaction's inputs
+    let mut spent: HashMap<&str, i128> = HashMap::new();
+    for input in data.inputs.iter() {
+        let amount = match input.input_type {
+            INPUT_COIN if is_base_asset(&input.asset_id) => amount(&input.amount),
+            // messages without data are coins of the base asset
+            INPUT_MESSAGE
+                if input
+                    .data
+                    .as_deref()
+                    .is_none_or(|data| data.trim_start_matches("0x").is_empty()) =>
+            {
+                amount(&input.amount)
+            }
+            _ => 0,
+        };
+        *spent.entry(input.tx_id.as_str()).or_default() += amount;
+    }
+    for output in data.outputs.iter() {
+        if matches!(
+            output.output_type,
+            OUTPUT_COIN | OUTPUT_CHANGE | OUTPUT_VARIABLE
+        ) && is_base_asset(&output.asset_id)
+        {
+            if let Some(spent) = spent.get_mut(output.tx_id.as_str()) {
+                *spent -= amount(&output.amount);
+            }
+        }
+    }
+    // the movements of a reverted script are rolled back
+    let succeeded: HashSet<&str> = data
+        .receipts
+        .iter()
+        .filter(|receipt| receipt.receipt_type == SCRIPT_RESULT && receipt.result == Some(0))
+        .map(|receipt| receipt.tx_id.as_str())
+        .collect();
+    for receipt in data.receipts.iter() {
+        if !succeeded.contains(receipt.tx_id.as_str()) {
+            continue;
+        }
+        let moved = match receipt.receipt_type {
+            CALL | TRANSFER
+                if is_script(&receipt.contract_id) && is_base_asset(&receipt.asset_id) =>
+            {
+                amount(&receipt.amount)
+            }
+            TRANSFER_OUT
+                if !is_script(&receipt.contract_id) && is_base_asset(&receipt.asset_id) =>
+            {
+                -amount(&receipt.amount)
+            }
+            MESSAGE_OUT if is_script(&receipt.sender) => amount(&receipt.amount),
+            _ => 0,
+        };
+        if let Some(spent) = spent.get_mut(receipt.tx_id.as_str()) {
+            *spent -= moved;
+        }
+    }
+
+    let mut transactions = Vec::new();
+    for tx in data.transactions.iter() {
+        if tx.tx_type == TX_TYPE_MINT {
+            continue;
+        }
+        let block = blocks.entry(tx.block_height).or_default();
+
+        let script_gas_used = script_gas.get(tx.id.as_str()).copied();
+        let predicate_gas_used = predicate_gas.get(tx.id.as_str()).copied().unwrap_or(0);
+        let gas_used = script_gas_used.unwrap_or(0) + predicate_gas_used;
+        let tip = tx.policies_tip.unwrap_or(0);
+
+        let execution_fee = block.gas_price.map(|gas_price| {
+            (gas_used.max(0) as u128 * gas_price.max(0) as u128).div_ceil(factor) as i128
+        });
+        let fee = spent.get(tx.id.as_str()).copied();
+        let execution_fee_plus_tip = execution_fee.map(|execution_fee| {
+            let fee = execution_fee + i128::from(tip);
+            match tx.policies_max_fee {
+                Some(max_fee) => fee.min(i128::from(max_fee)),
+                None => fee,
+            }
+        });
+
+        block.transaction_count += 1;
+        block.gas_used += gas_used;
+        block.tips += i128::from(tip);
+        block.execution_fees_plus_tips += execution_fee_plus_tip.unwrap_or(0);
+        block.fees += fee.unwrap_or(0);
+
+        transactions.push(TransactionFee {
+            tx_id: tx.id.clone(),
+            block_height: tx.block_height,
+            tx_type: tx.tx_type,
+            status: tx.status,
+            gas_price: block.gas_price,
+            script_gas_used,
+            predicate_gas_used,
+            gas_used,
+            tip,
+            max_fee: tx.policies_max_fee,
+            execution_fee: execution_fee.map(BigInt::from),
+            execution_fee_plus_tip: execution_fee_plus_tip.map(BigInt::from),
+            fee: fee.map(BigInt::from),
+        });
+    }
+
+    let blocks = blocks
+        .into_iter()
+        .map(|(block_height, block)| BlockFees {
+            block_height,
+            gas_price: block.gas_price,
+            transaction_count: block.transaction_count,
+            gas_used: block.gas_used,
+            tips: block.tips.into(),
+            execution_fees_plus_tips: block.execution_fees_plus_tips.into(),
+            fees: block.fees.into(),
+            fees_collected: block.fees_collected.map(BigInt::from),
+        })
+        .collect();
+
+    Ok(FeeReport {
+        transactions,
+        blocks,
+    })
+}
+
+fn amount(amount: &Option<BigInt>) -> i128 {
+    amount
+        .as_ref()
+        .map_or(0, |amount| amount.get_u64().1 as i128)
+}
+
+/// Whether the receipt was made by the script, which has the zero contract id.
+fn is_script(contract_id: &Option<String>) -> bool {
+    contract_id.as_deref().is_none_or(|contract_id| {
+        contract_id
+            .trim_start_matches("0x")
+            .bytes()
+            .all(|b| b == b'0')
+    })
+}
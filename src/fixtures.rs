@@ -0,0 +1,234 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{config::Config, transport::StatusError};
+
+/// Request as it is stored in a fixture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path relative to the url of the instance, e.g. "query/arrow-ipc"
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    request: RecordedRequest,
+    /// Status code of the response, the response body is stored next to the fixture
+    status: u16,
+}
+
+/// A replayed request has no matching recording, retrying it can't succeed.
+#[derive(Debug)]
+pub struct FixtureError(anyhow::Error);
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "replay fixture")
+    }
+}
+
+impl std::error::Error for FixtureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// Records request/response pairs into a fixtures directory or serves them back in order.
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: Mode,
+    next: Mutex<usize>,
+}
+
+impl Fixtures {
+    /// Open the fixtures configured in `cfg`, returns `None` if neither mode is enabled.
+    ///
+    /// Existing fixtures in the directory are removed when recording.
+    pub fn from_config(cfg: &Config) -> Result<Option<Self>> {
+        let mode = match cfg.fixtures_mode.as_deref() {
+            None => return Ok(None),
+            Some("record") => Mode::Record,
+            Some("replay") => Mode::Replay,
+            Some(mode) => return Err(anyhow!("unknown fixtures mode {}", mode)),
+        };
+        let dir = PathBuf::from(
+            cfg.fixtures_dir
+                .as_ref()
+                .context("fixtures_dir is required with fixtures_mode")?,
+        );
+
+        if let Mode::Record = mode {
+            fs::create_dir_all(&dir).context("create fixtures directory")?;
+            for entry in fs::read_dir(&dir).context("read fixtures directory")? {
+                let path = entry?.path();
+                if is_fixture_file(&path) {
+                    fs::remove_file(&path).context("remove old fixture")?;
+                }
+            }
+        }
+
+        Ok(Some(Self {
+            dir,
+            mode,
+            next: Mutex::new(0),
+        }))
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay)
+    }
+
+    /// Serve the next recorded response.
+    ///
+    /// Fails with a diff if the request doesn't match the one that was recorded.
+    pub fn replay(&self, request: &RecordedRequest) -> Result<Bytes> {
+        let (status, body) = self.next_recorded(request).map_err(FixtureError)?;
+
+        if !status.is_success() {
+            return Err(StatusError {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+                retry_after: None,
+            }
+            .into());
+        }
+
+        Ok(body.into())
+    }
+
+    fn next_recorded(&self, request: &RecordedRequest) -> Result<(StatusCode, Vec<u8>)> {
+        let mut next = self.next.lock().unwrap();
+        let (json_path, body_path) = self.paths(*next);
+
+        let fixture = fs::read(&json_path).with_context(|| {
+            format!(
+                "no recorded response left for request #{}, expected {}",
+                *next,
+                json_path.display()
+            )
+        })?;
+        let fixture: Fixture = serde_json::from_slice(&fixture)
+            .with_context(|| format!("parse fixture {}", json_path.display()))?;
+
+        if fixture.request != *request {
+            return Err(anyhow!(
+                "request #{} doesn't match the recorded request in {}:\n{}",
+                *next,
+                json_path.display(),
+                diff(&fixture.request, request)
+            ));
+        }
+
+        let body = fs::read(&body_path)
+            .with_context(|| format!("read recorded response {}", body_path.display()))?;
+        *next += 1;
+
+        let status = StatusCode::from_u16(fixture.status).context("parse recorded status")?;
+
+        Ok((status, body))
+    }
+
+    /// Save the outcome of a request.
+    ///
+    /// Successful responses and error responses from the server are recorded, requests that
+    /// didn't get a response are not.
+    pub fn record(
+        &self,
+        request: &RecordedRequest,
+        res: Result<&Bytes, &anyhow::Error>,
+    ) -> Result<()> {
+        let (status, body) = match res {
+            Ok(bytes) => (StatusCode::OK, bytes.to_vec()),
+            Err(e) => match e.downcast_ref::<StatusError>() {
+                Some(e) => (e.status, e.body.as_bytes().to_vec()),
+                None => return Ok(()),
+            },
+        };
+
+        let mut next = self.next.lock().unwrap();
+        let (json_path, body_path) = self.paths(*next);
+
+        let fixture = Fixture {
+            request: request.clone(),
+            status: status.as_u16(),
+        };
+        let fixture = serde_json::to_vec_pretty(&fixture).context("serialize fixture")?;
+        fs::write(&json_path, fixture).context("write fixture")?;
+        fs::write(&body_path, body).context("write recorded response")?;
+        *next += 1;
+
+        Ok(())
+    }
+
+    fn paths(&self, index: usize) -> (PathBuf, PathBuf) {
+        (
+            self.dir.join(format!("{:05}.json", index)),
+            self.dir.join(format!("{:05}.bin", index)),
+        )
+    }
+}
+
+fn is_fixture_file(path: &Path) -> bool {
+    let is_numbered = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| !stem.is_empty() && stem.bytes().all(|b| b.is_ascii_digit()));
+    let ext = path.extension().and_then(|ext| ext.to_str());
+
+    is_numbered && matches!(ext, Some("json") | Some("bin"))
+}
+
+/// Line diff of the pretty printed requests, lines of the recorded request are prefixed
+/// with "-" and lines of the live request with "+".
+fn diff(recorded: &RecordedRequest, live: &RecordedRequest) -> String {
+    let recorded = serde_json::to_string_pretty(recorded).unwrap_or_default();
+    let live = serde_json::to_string_pretty(live).unwrap_or_default();
+    let a: Vec<&str> = recorded.lines().collect();
+    let b: Vec<&str> = live.lines().collect();
+
+    // longest common subsequence table, lcs[i][j] is the lcs of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out += &format!("  {}\n", a[i]);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out += &format!("+ {}\n", b[j]);
+            j += 1;
+        } else {
+            out += &format!("- {}\n", a[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
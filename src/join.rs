@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use hyperfuel_net_types::Query;
+
+use crate::{
+    response::QueryResponseDataTyped,
+    types::{Block, Input, Output, Receipt, Transaction},
+};
+
+/// A transaction along with its block and everything it produced.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransactionContext {
+    pub transaction: Transaction,
+    /// Header of the block the transaction is in, not set if the response didn't contain it
+    pub block: Option<Block>,
+    /// Receipts of the transaction ordered by receipt index
+    pub receipts: Vec<Receipt>,
+    /// Inputs of the transaction in the order they appear in the transaction
+    pub inputs: Vec<Input>,
+    /// Outputs of the transaction in the order they appear in the transaction
+    pub outputs: Vec<Output>,
+}
+
+/// Select the fields that are needed to join the response data.
+pub fn add_join_fields_to_field_selection(query: &mut Query) {
+    let fields = &mut query.field_selection;
+    fields.block.insert("height".into());
+    fields.transaction.insert("id".into());
+    fields.transaction.insert("block_height".into());
+    fields.receipt.insert("tx_id".into());
+    fields.receipt.insert("receipt_index".into());
+    fields.input.insert("tx_id".into());
+    fields.output.insert("tx_id".into());
+}
+
+/// Nest the receipts, inputs, outputs and block of each transaction under it.
+///
+/// Transactions are returned in the order of the response. Receipts, inputs and outputs of
+/// transactions that aren't in the response are dropped.
+pub fn join_transactions(data: QueryResponseDataTyped) -> Vec<TransactionContext> {
+    let blocks: HashMap<i64, Block> = data
+        .blocks
+        .into_iter()
+        .map(|block| (block.height, block))
+        .collect();
+
+    let mut txs: Vec<TransactionContext> = data
+        .transactions
+        .into_iter()
+        .map(|transaction| TransactionContext {
+            block: blocks.get(&transaction.block_height).cloned(),
+            transaction,
+            receipts: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        })
+        .collect();
+
+    let positions: HashMap<String, usize> = txs
+        .iter()
+        .enumerate()
+        .map(|(pos, tx)| (tx.transaction.id.clone(), pos))
+        .collect();
+
+    for receipt in data.receipts {
+        if let Some(&pos) = positions.get(&receipt.tx_id) {
+            txs[pos].receipts.push(receipt);
+        }
+    }
+    for input in data.inputs {
+        if let Some(&pos) = positions.get(&input.tx_id) {
+            txs[pos].inputs.push(input);
+        }
+    }
+    for output in data.outputs {
+        if let Some(&pos) = positions.get(&output.tx_id) {
+            txs[pos].outputs.push(output);
+        }
+    }
+
+    for tx in txs.iter_mut() {
+        tx.receipts.sort_by_key(|receipt| receipt.receipt_index);
+    }
+
+    txs
+}
@@ -1,20 +1,147 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use anyhow::{anyhow, Context, Result};
+use futures::StreamExt;
+use hyperfuel_client::{LogContext, QueryResponse};
+use hyperfuel_format::{Hash, Hex};
+use napi::{threadsafe_function::ThreadSafeCallContext, Env, JsFunction, JsUnknown};
 
+mod asset;
+mod auth;
+mod balances;
+mod bridge;
+mod cache;
+mod call_tree;
 mod config;
+mod decode;
+mod deployments;
+mod error;
+#[cfg(feature = "fault-injection")]
+mod faults;
+mod fees;
+mod filter;
+mod fixtures;
+mod from_arrow;
+mod join;
+mod metrics;
+mod mock_server;
+mod openmetrics;
+mod page_size;
+mod pages;
+mod parquet_out;
+mod predicates;
+mod preset_query;
 mod query;
+mod rate_limit;
 mod response;
+mod to_arrow;
+mod trace;
+mod transfers;
+mod transport;
 mod types;
+mod util;
+mod utxo;
 
+use asset::MintBurnEvent;
+use auth::TokenCallback;
+use balances::{BalanceOptions, BalanceTracker};
+use cache::ResponseCache;
+use call_tree::CallTree;
 use config::Config;
-use query::Query;
-use response::{LogResponse, QueryResponseTyped};
+use deployments::ContractDeploymentFilter;
+use error::{Outcome, RateLimited};
+use fees::{FeeParameters, FeeReport};
+use metrics::{as_millis, ClientMetrics, MetricsRecorder, RequestMetrics, Timed};
+use openmetrics::ClientStats;
+use page_size::PageTarget;
+use predicates::PredicateTracker;
+use query::{BatchOptions, ParallelOptions, Query};
+use response::{
+    BalanceResponse, BatchResult, BridgeMessageResponse, ContractDeploymentResponse, LogResponse,
+    MintBurnResponse, PredicateActivityResponse, QueryResponseDataTyped, QueryResponseTyped,
+    TransactionsResponse, TransferResponse, UtxoLifecycleResponse,
+};
+use trace::{Span, SpanData, SpanExporter, SpanHandler};
+use transport::Transport;
+use utxo::{UtxoId, UtxoTracker};
 
 #[macro_use]
 extern crate napi_derive;
 
+const DEFAULT_BATCH_CONCURRENCY: i64 = 4;
+
+/// Render the statistics of all clients that were created with a `metricsLabel`
+/// in OpenMetrics text format.
+#[napi]
+pub fn render_metrics() -> String {
+    openmetrics::render()
+}
+
+/// Rebuild the call tree of every transaction from its receipts.
+///
+/// Each call frame carries the logs and transfers emitted in it, its return value and
+/// whether it reverted or panicked. The receipts of a transaction have to be complete,
+/// so don't filter them by type or contract in the query.
+#[napi]
+pub fn build_call_trees(receipts: Vec<types::Receipt>) -> Vec<CallTree> {
+    call_tree::build_call_trees(receipts)
+}
+
+/// Compute the gas used, tip and fee of every transaction in the response data, along with
+/// the totals of each block.
+///
+/// The gas price of a block is taken from its mint transaction, so the data has to contain
+/// the mint transactions with `mint_gas_price` and `mint_amount` selected. Transactions need
+/// `id`, `block_height`, `tx_type`, `status`, `policies_tip` and `policies_max_fee`.
+/// ScriptResult receipts with `gas_used` and `result` and inputs with `predicate_gas_used`
+/// provide the gas used.
+///
+/// The fee paid is computed from the base asset of the inputs and outputs, so all inputs and
+/// outputs of the transactions need `input_type`/`output_type`, `amount`, `asset_id` and the
+/// `data` of message inputs. Call, Transfer, TransferOut and MessageOut receipts with
+/// `contract_id`, `sender`, `amount` and `asset_id` account for the base asset moved into and
+/// out of contracts. The execution fee plus tip leaves out the fee for the size of a
+/// transaction and is only a lower bound.
+#[napi]
+pub fn compute_fees(
+    data: response::QueryResponseDataTyped,
+    params: FeeParameters,
+) -> napi::Result<FeeReport> {
+    fees::compute_fees(data, &params)
+        .context("compute fees")
+        .map_err(error::to_napi)
+}
+
+/// Decode a utxo id into the transaction that created the coin and the index of its output.
+///
+/// The output index isn't set for 32 byte utxo ids, which only contain the transaction id.
+#[napi]
+pub fn decode_utxo_id(utxo_id: String) -> napi::Result<UtxoId> {
+    utxo::decode_utxo_id(&utxo_id)
+        .context("decode utxo id")
+        .map_err(error::to_napi)
+}
+
 #[napi]
 pub struct HyperfuelClient {
-    inner: hyperfuel_client::Client,
+    transport: Transport,
+    cache: Option<ResponseCache>,
+    metrics: Arc<MetricsRecorder>,
+    stats: Option<Arc<ClientStats>>,
+    spans: Arc<SpanExporter>,
+    page_target: Option<PageTarget>,
+}
+
+/// Progress of a paginated operation, see `HyperfuelClient::paginate`.
+struct Pagination {
+    archive_height: Option<i64>,
+    next_block: i64,
+    total_execution_time: i64,
+    /// End of the range, capped at the head of the chain
+    to_block: u64,
 }
 
 #[napi]
@@ -24,27 +151,86 @@ impl HyperfuelClient {
     pub fn new(cfg: Config) -> napi::Result<HyperfuelClient> {
         env_logger::try_init().ok();
 
-        Self::new_impl(cfg).map_err(|e| napi::Error::from_reason(format!("{:?}", e)))
+        Self::new_impl(cfg).map_err(error::to_napi)
     }
 
     fn new_impl(cfg: Config) -> Result<HyperfuelClient> {
-        let cfg = cfg.try_convert().context("parse config")?;
+        let stats = cfg.metrics_label.as_deref().map(openmetrics::register);
+        let transport = Transport::new(&cfg, stats.clone()).context("build transport")?;
+        let cache = ResponseCache::from_config(&cfg).context("open response cache")?;
+        let page_target = PageTarget::from_config(&cfg).context("parse page size target")?;
+
+        Ok(HyperfuelClient {
+            transport,
+            cache,
+            metrics: Default::default(),
+            stats,
+            spans: Arc::new(SpanExporter::default()),
+            page_target,
+        })
+    }
+
+    /// Register a callback that receives the tracing spans of this client's operations
+    /// after they finish.
+    ///
+    /// Span ids follow the W3C trace context format, so the spans can be forwarded to an
+    /// OpenTelemetry exporter.
+    #[napi(ts_args_type = "callback: (span: SpanData) => void")]
+    pub fn on_span(&self, env: Env, callback: JsFunction) -> napi::Result<()> {
+        let mut handler: SpanHandler = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<SpanData>| {
+                Ok(vec![ctx.value])
+            })?;
+        // don't keep the process alive just because a span handler is registered
+        handler.unref(&env)?;
 
-        let inner = hyperfuel_client::Client::new(cfg).context("build client")?;
+        self.spans.set_handler(handler);
 
-        Ok(HyperfuelClient { inner })
+        Ok(())
     }
 
-    /// Get the height of the source hyperfuel instance
+    /// Get bearer tokens from the given callback instead of the config.
+    ///
+    /// The callback is called before the first request and again whenever the server rejects
+    /// the token with HTTP 401, in which case the request is retried once with the new token.
+    #[napi(ts_args_type = "callback: () => string | Promise<string>")]
+    pub fn set_bearer_token_provider(&self, env: Env, callback: JsFunction) -> napi::Result<()> {
+        let mut callback: TokenCallback = callback
+            .create_threadsafe_function(0, |_: ThreadSafeCallContext<()>| {
+                Ok(Vec::<JsUnknown>::new())
+            })?;
+        callback.unref(&env)?;
+
+        self.transport.tokens().set_callback(callback);
+
+        Ok(())
+    }
+
+    /// Get cumulative client side metrics of all requests made by this client
     #[napi]
-    pub async fn get_height(&self) -> napi::Result<i64> {
-        let height = self
-            .inner
-            .get_height()
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))?;
+    pub fn metrics(&self) -> ClientMetrics {
+        self.metrics.snapshot()
+    }
 
-        Ok(height.try_into().unwrap())
+    /// Get the height of the source hyperfuel instance
+    #[napi(ts_return_type = "Promise<number>")]
+    pub async fn get_height(&self) -> napi::Result<Outcome<i64>> {
+        let height = self.get_height_impl().await;
+
+        Ok(Outcome(height.map(|height| height.try_into().unwrap())))
+    }
+
+    async fn get_height_impl(&self) -> Result<u64> {
+        let res = self.transport.get_height().await;
+        self.observe(res.as_ref().map(|(_, metrics)| metrics));
+        let (height, metrics) = res?;
+
+        self.metrics.record(&metrics);
+        if let Some(stats) = &self.stats {
+            stats.set_archive_height(height);
+        }
+
+        Ok(height)
     }
 
     /// Get the height of the source hyperfuel instance
@@ -52,32 +238,80 @@ impl HyperfuelClient {
     /// On an error from the source hyperfuel instance, sleeps for
     /// 1 second (increasing by 1 each failure up to max of 5 seconds)
     /// and retries query until success.
-    #[napi]
-    pub async fn get_height_with_retry(&self) -> napi::Result<i64> {
-        let height = self
-            .inner
-            .get_height_with_retry()
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))?;
+    /// Fails without retrying on errors that retrying can't fix, like a request the server
+    /// rejected, a rate limit that outlasted the retries of the transport or a missing fixture.
+    #[napi(ts_return_type = "Promise<number>")]
+    pub async fn get_height_with_retry(&self) -> napi::Result<Outcome<i64>> {
+        let height = self.get_height_with_retry_impl().await;
 
-        Ok(height.try_into().unwrap())
+        Ok(Outcome(height.map(|height| height.try_into().unwrap())))
+    }
+
+    async fn get_height_with_retry_impl(&self) -> Result<u64> {
+        let mut base = 1;
+
+        loop {
+            let retry_after = match self.get_height_impl().await {
+                Ok(res) => return Ok(res),
+                Err(e) if transport::is_fatal(&e) => return Err(e),
+                Err(e) => {
+                    log::error!(
+                        "failed to send request to hyperfuel server: {}",
+                        auth::redact_error(&e)
+                    );
+                    transport::retry_after(&e)
+                }
+            };
+
+            if let Some(stats) = &self.stats {
+                stats.record_retry();
+            }
+
+            let secs = Duration::from_secs(base);
+            let millis = Duration::from_millis(rand::random::<u64>() % 1000);
+
+            tokio::time::sleep(retry_after.unwrap_or_default().max(secs + millis)).await;
+
+            base = std::cmp::min(base + 1, 5);
+        }
     }
 
     /// Create a parquet file by executing a query.
     ///
     /// Path should point to a folder that will contain the parquet files in the end.
-    #[napi]
-    pub async fn create_parquet_folder(&self, query: Query, path: String) -> napi::Result<()> {
-        self.create_parquet_folder_impl(query, path)
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))
+    ///
+    /// If a W3C traceparent is given, the spans of this operation continue that trace.
+    ///
+    /// If `parallel` is given, the block range is split into sub-ranges that are fetched
+    /// in parallel. The files are still written in block order.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async fn create_parquet_folder(
+        &self,
+        query: Query,
+        path: String,
+        traceparent: Option<String>,
+        parallel: Option<ParallelOptions>,
+    ) -> napi::Result<Outcome<()>> {
+        let mut span = self.query_span("create_parquet_folder", traceparent, &query);
+        let res = self
+            .create_parquet_folder_impl(query, path, parallel, &span)
+            .await;
+        if let Err(e) = &res {
+            span.set_error(e);
+        }
+        Ok(Outcome(res))
     }
 
-    async fn create_parquet_folder_impl(&self, query: Query, path: String) -> Result<()> {
+    async fn create_parquet_folder_impl(
+        &self,
+        query: Query,
+        path: String,
+        parallel: Option<ParallelOptions>,
+        span: &Span,
+    ) -> Result<()> {
         let query = query.try_convert().context("parse query")?;
 
-        self.inner
-            .create_parquet_folder(query, path)
+        parquet_out::create_parquet_folder(self, query, path, parallel.as_ref(), span)
             .await
             .context("create parquet folder")?;
 
@@ -92,38 +326,168 @@ impl HyperfuelClient {
     /// and applies the field selection to all these loaded transactions.  So your query will return the data you
     /// want plus additional data from the loaded transactions.  This functionality is in case you want to associate
     /// receipts, inputs, or outputs with eachother.
-    #[napi]
-    pub async fn get_data(&self, query: Query) -> napi::Result<QueryResponseTyped> {
-        self.get_data_impl(query)
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))
+    ///
+    /// If a W3C traceparent is given, the spans of this operation continue that trace.
+    #[napi(ts_return_type = "Promise<QueryResponseTyped>")]
+    pub async fn get_data(
+        &self,
+        query: Query,
+        traceparent: Option<String>,
+    ) -> napi::Result<Timed<Outcome<QueryResponseTyped>>> {
+        let mut span = self.query_span("get_data", traceparent, &query);
+        let res = self.get_data_impl(query, &span).await;
+        if let Err(e) = &res {
+            span.set_error(e);
+        }
+        Ok(self.timed(Outcome(res)))
     }
 
-    async fn get_data_impl(&self, query: Query) -> Result<QueryResponseTyped> {
+    async fn get_data_impl(&self, query: Query, span: &Span) -> Result<QueryResponseTyped> {
         let query = query.try_convert().context("parse query")?;
-        let resp = self.inner.get_data(&query).await.context("get data")?;
-        Ok(resp.into())
+        let (res, metrics) = self
+            .get_arrow_data(&query, span, 0)
+            .await
+            .context("get data")?;
+        self.convert_typed(res, metrics)
+    }
+
+    /// Send multiple queries at once, same as calling `getData` with each of them.
+    ///
+    /// Up to `options.concurrency` queries run at the same time (default is 4). Results are
+    /// returned in the order of the queries, a query that fails doesn't fail the others.
+    ///
+    /// If a W3C traceparent is given, the spans of every query continue that trace.
+    #[napi(ts_return_type = "Promise<Array<BatchResult>>")]
+    pub async fn get_data_batch(
+        &self,
+        queries: Vec<Query>,
+        options: Option<BatchOptions>,
+        traceparent: Option<String>,
+    ) -> napi::Result<Timed<Vec<BatchResult>>> {
+        let concurrency = options
+            .and_then(|o| o.concurrency)
+            .unwrap_or(DEFAULT_BATCH_CONCURRENCY);
+        let concurrency = usize::try_from(concurrency)
+            .ok()
+            .filter(|&c| c > 0)
+            .ok_or_else(|| napi::Error::from_reason("concurrency has to be positive"))?;
+
+        let results = futures::stream::iter(queries.into_iter().enumerate())
+            .map(|(index, query)| {
+                let traceparent = traceparent.clone();
+                async move {
+                    let mut span = self.query_span("get_data", traceparent, &query);
+                    span.set("batch_index", index);
+                    match self.get_data_impl(query, &span).await {
+                        Ok(response) => BatchResult {
+                            response: Some(response),
+                            error: None,
+                            rate_limited: None,
+                        },
+                        Err(e) => {
+                            span.set_error(&e);
+                            BatchResult {
+                                response: None,
+                                rate_limited: RateLimited::from_error(&e),
+                                error: Some(error::to_napi(e).reason),
+                            }
+                        }
+                    }
+                }
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        Ok(self.timed(results))
     }
 
     /// Send a query request to the source hyperfuel instance.
     ///
     /// Returns a query response that which contains structured data that doesn't include any inputs, outputs,
     /// and receipts that don't exactly match the query's input, outout, or receipt selection.
-    #[napi]
-    pub async fn get_selected_data(&self, query: Query) -> napi::Result<QueryResponseTyped> {
-        self.get_selected_data_impl(query)
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))
+    ///
+    /// If a W3C traceparent is given, the spans of this operation continue that trace.
+    #[napi(ts_return_type = "Promise<QueryResponseTyped>")]
+    pub async fn get_selected_data(
+        &self,
+        query: Query,
+        traceparent: Option<String>,
+    ) -> napi::Result<Timed<Outcome<QueryResponseTyped>>> {
+        let mut span = self.query_span("get_selected_data", traceparent, &query);
+        let res = self.get_selected_data_impl(query, &span).await;
+        if let Err(e) = &res {
+            span.set_error(e);
+        }
+        Ok(self.timed(Outcome(res)))
     }
 
-    async fn get_selected_data_impl(&self, query: Query) -> Result<QueryResponseTyped> {
+    async fn get_selected_data_impl(
+        &self,
+        query: Query,
+        span: &Span,
+    ) -> Result<QueryResponseTyped> {
         let query = query.try_convert().context("parse query")?;
-        let resp = self
-            .inner
-            .get_selected_data(&query)
+        let query = filter::add_selections_to_field_selection(&mut query.clone());
+        let (mut res, mut metrics) = self
+            .get_arrow_data(&query, span, 0)
             .await
             .context("get data")?;
-        Ok(resp.into())
+
+        let start = Instant::now();
+        res.data = filter::filter_out_unselected_data(res.data, &query)
+            .context("filter out unselected data")?;
+        metrics.decode_time_ms += as_millis(start.elapsed());
+
+        self.convert_typed(res, metrics)
+    }
+
+    /// Send a query request to the source hyperfuel instance.
+    ///
+    /// Returns the transactions of the response, each with its block header and its receipts,
+    /// inputs and outputs nested under it. The fields needed to join the data are added to
+    /// the field selection.
+    ///
+    /// If a W3C traceparent is given, the spans of this operation continue that trace.
+    #[napi(ts_return_type = "Promise<TransactionsResponse>")]
+    pub async fn get_transactions(
+        &self,
+        query: Query,
+        traceparent: Option<String>,
+    ) -> napi::Result<Timed<Outcome<TransactionsResponse>>> {
+        let mut span = self.query_span("get_transactions", traceparent, &query);
+        let res = self.get_transactions_impl(query, &span).await;
+        if let Err(e) = &res {
+            span.set_error(e);
+        }
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn get_transactions_impl(
+        &self,
+        query: Query,
+        span: &Span,
+    ) -> Result<TransactionsResponse> {
+        let mut query = query.try_convert().context("parse query")?;
+        join::add_join_fields_to_field_selection(&mut query);
+        let (res, metrics) = self
+            .get_arrow_data(&query, span, 0)
+            .await
+            .context("get data")?;
+        let res = self.convert_typed(res, metrics)?;
+
+        let start = Instant::now();
+        let data = join::join_transactions(res.data);
+        let mut metrics = res.metrics;
+        metrics.conversion_time_ms += as_millis(start.elapsed());
+
+        Ok(TransactionsResponse {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data,
+            metrics,
+        })
     }
 
     /// Send a query request to the source hyperfuel instance.
@@ -136,16 +500,17 @@ impl HyperfuelClient {
     /// were a part of a failed transaction.
     ///
     /// NOTE: this function is experimental and might be removed in future versions.
-    #[napi]
+    #[napi(ts_return_type = "Promise<LogResponse>")]
     pub async fn preset_query_get_logs(
         &self,
         emitting_contracts: Vec<String>,
         from_block: i64,
         to_block: Option<i64>,
-    ) -> napi::Result<LogResponse> {
-        self.preset_query_get_logs_impl(emitting_contracts, from_block, to_block)
-            .await
-            .map_err(|e| napi::Error::from_reason(format!("{:?}", e)))
+    ) -> napi::Result<Timed<Outcome<LogResponse>>> {
+        let res = self
+            .preset_query_get_logs_impl(emitting_contracts, from_block, to_block)
+            .await;
+        Ok(self.timed(Outcome(res)))
     }
 
     async fn preset_query_get_logs_impl(
@@ -154,29 +519,826 @@ impl HyperfuelClient {
         from_block: i64,
         to_block: Option<i64>,
     ) -> Result<LogResponse> {
-        // cut the "0x" off the address
-        let mut emitting_contracts_args = vec![];
-        for contract_address in emitting_contracts {
-            let address: &str = if &contract_address[..2] == "0x" {
-                &contract_address[2..]
-            } else {
-                &contract_address
-            };
-            let address = hex_str_address_to_byte_array(address)
-                .context(format!("convert address {}", address))?;
-            emitting_contracts_args.push(address)
-        }
+        let emitting_contracts_args = parse_addresses(emitting_contracts)?;
 
         let from_block = from_block as u64;
         let to_block = to_block.map(|i| i as u64);
 
-        let resp = self
-            .inner
-            .preset_query_get_logs(emitting_contracts_args, from_block, to_block)
+        let query = preset_query::logs(emitting_contracts_args, from_block, to_block);
+        let span = Span::root("preset_query_get_logs", None, self.spans.clone());
+        let (res, mut metrics) = self
+            .get_arrow_data(&query, &span, 0)
             .await
             .context("get logs")?;
-        Ok(resp.into())
+
+        let start = Instant::now();
+        let filtered_data = filter::filter_out_unselected_data(res.data, &query)
+            .context("filter out unselected receipts")?;
+        let typed_receipts = from_arrow::receipts_from_arrow_data(&filtered_data.receipts)
+            .context("convert arrow data to receipt response")?;
+        metrics.decode_time_ms += as_millis(start.elapsed());
+
+        let start = Instant::now();
+        let mut resp: LogResponse = hyperfuel_client::LogResponse {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data: typed_receipts.into_iter().map(LogContext::from).collect(),
+        }
+        .into();
+        metrics.conversion_time_ms = as_millis(start.elapsed());
+
+        self.metrics.record(&metrics);
+        resp.metrics = metrics;
+
+        Ok(resp)
+    }
+
+    /// Send a query request to the source hyperfuel instance.
+    ///
+    /// Returns every movement of the given assets from or to any of the given addresses or
+    /// contracts within the block range, normalized from Transfer and TransferOut receipts,
+    /// coin inputs and coin, change and variable outputs. Lists that are not given or empty
+    /// match everything.
+    /// Transfers to contracts are only found in transactions that match otherwise, since
+    /// receipts can't be selected by their recipient contract.
+    /// If no 'to_block' is specified, query will run to the head of the chain.
+    /// Transfers of failed transactions are left out unless `includeFailed` is set.
+    ///
+    /// NOTE: this function is experimental and might be removed in future versions.
+    #[napi(ts_return_type = "Promise<TransferResponse>")]
+    pub async fn preset_query_get_transfers(
+        &self,
+        asset_ids: Option<Vec<String>>,
+        addresses: Option<Vec<String>>,
+        contracts: Option<Vec<String>>,
+        from_block: i64,
+        to_block: Option<i64>,
+        include_failed: Option<bool>,
+    ) -> napi::Result<Timed<Outcome<TransferResponse>>> {
+        let res = self
+            .preset_query_get_transfers_impl(
+                asset_ids.unwrap_or_default(),
+                addresses.unwrap_or_default(),
+                contracts.unwrap_or_default(),
+                from_block,
+                to_block,
+                include_failed.unwrap_or(false),
+            )
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn preset_query_get_transfers_impl(
+        &self,
+        asset_ids: Vec<String>,
+        addresses: Vec<String>,
+        contracts: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+        include_failed: bool,
+    ) -> Result<TransferResponse> {
+        let asset_ids = parse_addresses(asset_ids).context("parse asset ids")?;
+        let addresses = parse_addresses(addresses).context("parse addresses")?;
+        let contracts = parse_addresses(contracts).context("parse contracts")?;
+
+        let filter = transfers::TransferFilter {
+            asset_ids: asset_ids.iter().map(|id| id.encode_hex()).collect(),
+            parties: addresses
+                .iter()
+                .chain(contracts.iter())
+                .map(|address| address.encode_hex())
+                .collect(),
+            include_failed,
+        };
+
+        let from_block = from_block as u64;
+        let to_block = to_block.map(|i| i as u64);
+
+        let query = preset_query::transfers(
+            asset_ids,
+            addresses,
+            contracts,
+            from_block,
+            to_block,
+            include_failed,
+        );
+        let span = Span::root("preset_query_get_transfers", None, self.spans.clone());
+        let (res, metrics) = self
+            .get_arrow_data(&query, &span, 0)
+            .await
+            .context("get transfers")?;
+        let res = self.convert_typed(res, metrics)?;
+
+        let start = Instant::now();
+        let data = transfers::extract_transfers(res.data, &filter);
+        let mut metrics = res.metrics;
+        metrics.conversion_time_ms += as_millis(start.elapsed());
+
+        Ok(TransferResponse {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data,
+            metrics,
+        })
+    }
+
+    /// Send a query request to the source hyperfuel instance.
+    ///
+    /// Returns all Mint and Burn receipts of the specified contracts within the block range,
+    /// along with the id of the asset they minted or burned. Receipts without an amount are
+    /// left out.
+    /// If no 'to_block' is specified, query will run to the head of the chain.
+    /// This query doesn't return any receipts that were a part of a failed transaction.
+    ///
+    /// NOTE: this function is experimental and might be removed in future versions.
+    #[napi(ts_return_type = "Promise<MintBurnResponse>")]
+    pub async fn preset_query_get_mints_burns(
+        &self,
+        contracts: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+    ) -> napi::Result<Timed<Outcome<MintBurnResponse>>> {
+        let res = self
+            .preset_query_get_mints_burns_impl(contracts, from_block, to_block)
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn preset_query_get_mints_burns_impl(
+        &self,
+        contracts: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+    ) -> Result<MintBurnResponse> {
+        let contracts = parse_addresses(contracts)?;
+
+        let from_block = from_block as u64;
+        let to_block = to_block.map(|i| i as u64);
+
+        let query = preset_query::mints_burns(contracts, from_block, to_block);
+        let span = Span::root("preset_query_get_mints_burns", None, self.spans.clone());
+        let (res, mut metrics) = self
+            .get_arrow_data(&query, &span, 0)
+            .await
+            .context("get mints and burns")?;
+
+        let start = Instant::now();
+        let filtered_data = filter::filter_out_unselected_data(res.data, &query)
+            .context("filter out unselected receipts")?;
+        let typed_receipts = from_arrow::receipts_from_arrow_data(&filtered_data.receipts)
+            .context("convert arrow data to receipt response")?;
+        metrics.decode_time_ms += as_millis(start.elapsed());
+
+        let start = Instant::now();
+        let data = typed_receipts
+            .into_iter()
+            .filter_map(MintBurnEvent::from_receipt)
+            .collect();
+        metrics.conversion_time_ms = as_millis(start.elapsed());
+
+        self.metrics.record(&metrics);
+
+        Ok(MintBurnResponse {
+            archive_height: res.archive_height.map(|h| h as i64),
+            next_block: res.next_block as i64,
+            total_execution_time: res.total_execution_time as i64,
+            data,
+            metrics,
+        })
+    }
+
+    /// Send a query request to the source hyperfuel instance.
+    ///
+    /// Returns the contracts deployed by successful Create transactions within the block range.
+    /// If no 'to_block' is specified, query will run to the head of the chain.
+    ///
+    /// NOTE: this function is experimental and might be removed in future versions.
+    #[napi(ts_return_type = "Promise<ContractDeploymentResponse>")]
+    pub async fn preset_query_get_contract_deployments(
+        &self,
+        from_block: i64,
+        to_block: Option<i64>,
+        filters: Option<ContractDeploymentFilter>,
+    ) -> napi::Result<Timed<Outcome<ContractDeploymentResponse>>> {
+        let res = self
+            .preset_query_get_contract_deployments_impl(
+                from_block,
+                to_block,
+                filters.unwrap_or_default(),
+            )
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn preset_query_get_contract_deployments_impl(
+        &self,
+        from_block: i64,
+        to_block: Option<i64>,
+        filters: ContractDeploymentFilter,
+    ) -> Result<ContractDeploymentResponse> {
+        let contract_ids = parse_addresses(filters.contract_ids.unwrap_or_default())
+            .context("parse contract ids")?;
+
+        let from_block = from_block as u64;
+        let to_block = to_block.map(|i| i as u64);
+
+        let include_bytecode = filters.include_bytecode.unwrap_or(false);
+        let query = preset_query::contract_deployments(
+            contract_ids,
+            from_block,
+            to_block,
+            include_bytecode,
+        );
+        let span = Span::root(
+            "preset_query_get_contract_deployments",
+            None,
+            self.spans.clone(),
+        );
+        let (res, metrics) = self
+            .get_arrow_data(&query, &span, 0)
+            .await
+            .context("get contract deployments")?;
+        let res = self.convert_typed(res, metrics)?;
+
+        let start = Instant::now();
+        let data = deployments::extract_deployments(res.data, include_bytecode);
+        let mut metrics = res.metrics;
+        metrics.conversion_time_ms += as_millis(start.elapsed());
+
+        Ok(ContractDeploymentResponse {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data,
+            metrics,
+        })
+    }
+
+    /// Send a query request to the source hyperfuel instance.
+    ///
+    /// Returns the messages sent between the base layer and Fuel within the block range:
+    /// withdrawals from MessageOut receipts and deposits from InputMessage inputs.
+    /// Only messages from the given senders and to the given recipients are returned
+    /// if the lists are given.
+    /// If no 'to_block' is specified, query will run to the head of the chain.
+    /// This query doesn't return any messages that were a part of a failed transaction.
+    ///
+    /// NOTE: this function is experimental and might be removed in future versions.
+    #[napi(ts_return_type = "Promise<BridgeMessageResponse>")]
+    pub async fn preset_query_get_bridge_messages(
+        &self,
+        senders: Option<Vec<String>>,
+        recipients: Option<Vec<String>>,
+        from_block: i64,
+        to_block: Option<i64>,
+    ) -> napi::Result<Timed<Outcome<BridgeMessageResponse>>> {
+        let res = self
+            .preset_query_get_bridge_messages_impl(
+                senders.unwrap_or_default(),
+                recipients.unwrap_or_default(),
+                from_block,
+                to_block,
+            )
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn preset_query_get_bridge_messages_impl(
+        &self,
+        senders: Vec<String>,
+        recipients: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+    ) -> Result<BridgeMessageResponse> {
+        let senders = parse_addresses(senders).context("parse senders")?;
+        let recipients = parse_addresses(recipients).context("parse recipients")?;
+
+        let from_block = from_block as u64;
+        let to_block = to_block.map(|i| i as u64);
+
+        let query = preset_query::bridge_messages(senders, recipients, from_block, to_block);
+        let span = Span::root("preset_query_get_bridge_messages", None, self.spans.clone());
+        let (mut res, mut metrics) = self
+            .get_arrow_data(&query, &span, 0)
+            .await
+            .context("get bridge messages")?;
+
+        let start = Instant::now();
+        res.data = filter::filter_out_unselected_data(res.data, &query)
+            .context("filter out unselected data")?;
+        metrics.decode_time_ms += as_millis(start.elapsed());
+        let res = self.convert_typed(res, metrics)?;
+
+        let start = Instant::now();
+        let data = bridge::extract_messages(res.data);
+        let mut metrics = res.metrics;
+        metrics.conversion_time_ms += as_millis(start.elapsed());
+
+        Ok(BridgeMessageResponse {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data,
+            metrics,
+        })
+    }
+
+    /// Compute the balance history of the given addresses and contracts within the block range.
+    ///
+    /// Returns the net change and the running balance for every block that changed a balance,
+    /// per address or contract and asset. Balances start at zero at 'from_block', so they are
+    /// only absolute balances if 'from_block' is 0.
+    /// Coin inputs and outputs count towards the balances of addresses, Transfer and
+    /// TransferOut receipts count towards the balances of contracts. Only successful
+    /// transactions are counted.
+    /// Transfers to contracts are only found in transactions that match otherwise, since
+    /// receipts can't be selected by their recipient contract.
+    /// Every asset is tracked if no asset ids are given.
+    /// If no 'to_block' is specified, balances are computed up to the head of the chain.
+    /// If `options.parallel` is given, the block range is split into sub-ranges that are
+    /// fetched in parallel. Pages are still processed in block order.
+    #[napi(ts_return_type = "Promise<BalanceResponse>")]
+    pub async fn compute_balances(
+        &self,
+        addresses: Option<Vec<String>>,
+        contracts: Option<Vec<String>>,
+        asset_ids: Option<Vec<String>>,
+        from_block: i64,
+        to_block: Option<i64>,
+        options: Option<BalanceOptions>,
+    ) -> napi::Result<Timed<Outcome<BalanceResponse>>> {
+        let res = self
+            .compute_balances_impl(
+                addresses.unwrap_or_default(),
+                contracts.unwrap_or_default(),
+                asset_ids.unwrap_or_default(),
+                from_block,
+                to_block,
+                options.unwrap_or_default(),
+            )
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn compute_balances_impl(
+        &self,
+        addresses: Vec<String>,
+        contracts: Vec<String>,
+        asset_ids: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+        options: BalanceOptions,
+    ) -> Result<BalanceResponse> {
+        let addresses = parse_addresses(addresses).context("parse addresses")?;
+        let contracts = parse_addresses(contracts).context("parse contracts")?;
+        if addresses.is_empty() && contracts.is_empty() {
+            return Err(anyhow!("at least one address or contract is required"));
+        }
+        let asset_ids = parse_addresses(asset_ids).context("parse asset ids")?;
+
+        let from_block = from_block as u64;
+        let mut tracker = BalanceTracker::new(
+            addresses.iter().map(|a| a.encode_hex()).collect(),
+            contracts.iter().map(|c| c.encode_hex()).collect(),
+            asset_ids.iter().map(|a| a.encode_hex()).collect(),
+            &options,
+        )?;
+        let query =
+            preset_query::transfers(asset_ids, addresses, contracts, from_block, None, false);
+
+        let pagination = self
+            .paginate(
+                "compute_balances",
+                query,
+                to_block,
+                options.parallel,
+                |data| {
+                    tracker.add(data);
+                    Ok(())
+                },
+            )
+            .await?;
+
+        Ok(BalanceResponse {
+            archive_height: pagination.archive_height,
+            next_block: pagination.next_block,
+            total_execution_time: pagination.total_execution_time,
+            data: tracker.finish(from_block, pagination.to_block),
+        })
+    }
+
+    /// Collect the spends of the given predicate roots within the block range.
+    ///
+    /// Returns every coin and message input that was unlocked by one of the predicates,
+    /// with its bytecode, data and the gas it used, along with stats for each predicate
+    /// bytecode.
+    /// If no 'to_block' is specified, activity is collected up to the head of the chain.
+    /// If `parallel` is given, the block range is split into sub-ranges that are fetched
+    /// in parallel. Pages are still processed in block order.
+    #[napi(ts_return_type = "Promise<PredicateActivityResponse>")]
+    pub async fn get_predicate_activity(
+        &self,
+        predicate_roots: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+        parallel: Option<ParallelOptions>,
+    ) -> napi::Result<Timed<Outcome<PredicateActivityResponse>>> {
+        let res = self
+            .get_predicate_activity_impl(predicate_roots, from_block, to_block, parallel)
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn get_predicate_activity_impl(
+        &self,
+        predicate_roots: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+        parallel: Option<ParallelOptions>,
+    ) -> Result<PredicateActivityResponse> {
+        let roots = parse_addresses(predicate_roots).context("parse predicate roots")?;
+        if roots.is_empty() {
+            return Err(anyhow!("at least one predicate root is required"));
+        }
+
+        let mut tracker = PredicateTracker::new(roots.iter().map(|r| r.encode_hex()).collect());
+        let query = preset_query::predicate_spends(roots, from_block as u64, None);
+
+        let pagination = self
+            .paginate(
+                "get_predicate_activity",
+                query,
+                to_block,
+                parallel,
+                |data| tracker.add(data).context("collect predicate spends"),
+            )
+            .await?;
+
+        let (spends, stats) = tracker.finish();
+
+        Ok(PredicateActivityResponse {
+            archive_height: pagination.archive_height,
+            next_block: pagination.next_block,
+            total_execution_time: pagination.total_execution_time,
+            spends,
+            stats,
+        })
+    }
+
+    /// Track the coins of the given owners or the given utxos within the block range.
+    ///
+    /// Returns every matching coin that was created or spent within the range, along with the
+    /// transaction that created it and the transaction that spent it. Coins that were created
+    /// before the range only have the spending side set, coins that are still unspent at the
+    /// end of the range only have the creating side set.
+    /// The coins of every owner are scanned if no owners are given, so the range should be
+    /// kept short when tracking utxos only.
+    /// If no 'to_block' is specified, the lifecycles are tracked up to the head of the chain.
+    /// If `parallel` is given, the block range is split into sub-ranges that are fetched
+    /// in parallel. Pages are still processed in block order.
+    #[napi(ts_return_type = "Promise<UtxoLifecycleResponse>")]
+    pub async fn get_utxo_lifecycles(
+        &self,
+        owners: Option<Vec<String>>,
+        utxo_ids: Option<Vec<String>>,
+        from_block: i64,
+        to_block: Option<i64>,
+        parallel: Option<ParallelOptions>,
+    ) -> napi::Result<Timed<Outcome<UtxoLifecycleResponse>>> {
+        let res = self
+            .get_utxo_lifecycles_impl(
+                owners.unwrap_or_default(),
+                utxo_ids.unwrap_or_default(),
+                from_block,
+                to_block,
+                parallel,
+            )
+            .await;
+        Ok(self.timed(Outcome(res)))
+    }
+
+    async fn get_utxo_lifecycles_impl(
+        &self,
+        owners: Vec<String>,
+        utxo_ids: Vec<String>,
+        from_block: i64,
+        to_block: Option<i64>,
+        parallel: Option<ParallelOptions>,
+    ) -> Result<UtxoLifecycleResponse> {
+        let owners = parse_addresses(owners).context("parse owners")?;
+        let utxos = utxo_ids
+            .iter()
+            .map(|utxo_id| utxo::decode_utxo_id(utxo_id))
+            .collect::<Result<_>>()
+            .context("parse utxo ids")?;
+
+        let mut tracker = UtxoTracker::new(owners.iter().map(|o| o.encode_hex()).collect(), utxos);
+        let query = preset_query::utxos(owners, from_block as u64, None);
+
+        let pagination = self
+            .paginate("get_utxo_lifecycles", query, to_block, parallel, |data| {
+                tracker.add(data).context("track utxos")
+            })
+            .await?;
+
+        Ok(UtxoLifecycleResponse {
+            archive_height: pagination.archive_height,
+            next_block: pagination.next_block,
+            total_execution_time: pagination.total_execution_time,
+            data: tracker.finish(),
+        })
+    }
+
+    /// Fetch the pages of the query and pass the data of each page to 'on_page' in block order.
+    ///
+    /// 'to_block' is exclusive and capped at the head of the chain, pages are fetched up to the
+    /// head of the chain if it isn't given. Pages are fetched one after another unless
+    /// `parallel` options are given.
+    async fn paginate(
+        &self,
+        name: &str,
+        mut query: hyperfuel_net_types::Query,
+        to_block: Option<i64>,
+        parallel: Option<ParallelOptions>,
+        mut on_page: impl FnMut(QueryResponseDataTyped) -> Result<()>,
+    ) -> Result<Pagination> {
+        let height = self.get_height_with_retry_impl().await?;
+        let to_block = pages::cap_to_head(to_block.map(|to_block| to_block as u64), height);
+        query.to_block = Some(to_block);
+
+        let mut pagination = Pagination {
+            archive_height: None,
+            next_block: query.from_block as i64,
+            total_execution_time: 0,
+            to_block,
+        };
+
+        let span = Span::root(name, None, self.spans.clone());
+        let mut pages = match &parallel {
+            Some(options) => pages::parallel(self, query, to_block, options, &span)
+                .context("split query range")?
+                .boxed(),
+            None => pages::sequential(self, query, to_block, &span).boxed(),
+        };
+        while let Some(page) = pages.next().await {
+            let (res, metrics) = page?;
+            let res = self.convert_typed(res, metrics)?;
+            pagination.archive_height = res.archive_height;
+            pagination.next_block = res.next_block;
+            pagination.total_execution_time += res.total_execution_time;
+            on_page(res.data)?;
+        }
+
+        Ok(pagination)
+    }
+
+    /// Wrap a response to count its conversion into javascript values in the client metrics.
+    fn timed<T>(&self, value: T) -> Timed<T> {
+        Timed::new(value, self.metrics.clone())
+    }
+
+    /// Start the root span of an operation that executes the given query.
+    fn query_span(&self, name: &str, traceparent: Option<String>, query: &Query) -> Span {
+        let mut span = Span::root(name, traceparent.as_deref(), self.spans.clone());
+        span.set("from_block", query.from_block);
+        span.set("to_block", query.to_block);
+        span
+    }
+
+    /// Send the query and decode the response into arrow data.
+    ///
+    /// On an error from the source hyperfuel instance, sleeps for
+    /// 1 second (increasing by 1 each failure up to max of 5 seconds),
+    /// or as long as the server asked for if it rate limited the request,
+    /// and retries query until success.
+    /// Errors that retrying can't fix, see `transport::is_fatal`, are returned right away.
+    async fn get_arrow_data_with_retry(
+        &self,
+        query: &hyperfuel_net_types::Query,
+        parent: &Span,
+    ) -> Result<(QueryResponse, RequestMetrics)> {
+        let mut base = 1;
+        let mut attempt = 0;
+
+        loop {
+            let retry_after = match self.get_arrow_data(query, parent, attempt).await {
+                Ok(res) => return Ok(res),
+                Err(e) if transport::is_fatal(&e) => return Err(e),
+                Err(e) => {
+                    log::error!(
+                        "failed to send request to hyperfuel server: {}",
+                        auth::redact_error(&e)
+                    );
+                    transport::retry_after(&e)
+                }
+            };
+
+            if let Some(stats) = &self.stats {
+                stats.record_retry();
+            }
+
+            let secs = Duration::from_secs(base);
+            let millis = Duration::from_millis(rand::random::<u64>() % 1000);
+
+            tokio::time::sleep(retry_after.unwrap_or_default().max(secs + millis)).await;
+
+            base = std::cmp::min(base + 1, 5);
+            attempt += 1;
+        }
+    }
+
+    /// Send the query and decode the response into arrow data.
+    ///
+    /// The request is traced as a child span of the given span.
+    async fn get_arrow_data(
+        &self,
+        query: &hyperfuel_net_types::Query,
+        parent: &Span,
+        attempt: u64,
+    ) -> Result<(QueryResponse, RequestMetrics)> {
+        let mut span = parent.child("query");
+        span.set("from_block", query.from_block);
+        span.set("to_block", query.to_block);
+        span.set("retry_attempt", attempt);
+
+        let res = self.get_arrow_data_impl(query, &span).await;
+
+        match &res {
+            Ok((res, metrics)) => {
+                let data = &res.data;
+                let page_rows: usize = [
+                    &data.blocks,
+                    &data.transactions,
+                    &data.receipts,
+                    &data.inputs,
+                    &data.outputs,
+                ]
+                .iter()
+                .flat_map(|batches| batches.iter())
+                .map(|batch| batch.chunk.len())
+                .sum();
+
+                span.set("next_block", res.next_block);
+                span.set("page_rows", page_rows);
+                span.set("response_bytes", metrics.response_bytes);
+                span.set("endpoint", metrics.endpoint.as_str());
+                span.set("cache_hit", metrics.cache_hit);
+            }
+            Err(e) => span.set_error(e),
+        }
+
+        res
+    }
+
+    async fn get_arrow_data_impl(
+        &self,
+        query: &hyperfuel_net_types::Query,
+        span: &Span,
+    ) -> Result<(QueryResponse, RequestMetrics)> {
+        let cache_key = match &self.cache {
+            Some(cache) => Some(cache.key(query).context("build cache key")?),
+            None => None,
+        };
+
+        let cached = match (&self.cache, cache_key) {
+            (Some(cache), Some(key)) => tokio::task::block_in_place(|| cache.get(key))
+                .unwrap_or_else(|e| {
+                    log::warn!("failed to read response cache: {}", auth::redact_error(&e));
+                    None
+                }),
+            _ => None,
+        };
+
+        let (bytes, mut metrics, endpoint) = match cached {
+            Some(bytes) => {
+                let metrics = RequestMetrics {
+                    cache_hit: true,
+                    response_bytes: bytes.len() as i64,
+                    ..Default::default()
+                };
+                (bytes, metrics, None)
+            }
+            None => {
+                let raw = self.transport.query(query, &span.traceparent()).await;
+                self.observe(raw.as_ref().map(|raw| &raw.metrics));
+                let raw = raw.context("send query")?;
+                (raw.bytes, raw.metrics, Some(raw.endpoint))
+            }
+        };
+
+        let start = Instant::now();
+        let parsed = tokio::task::block_in_place(|| decode::parse_query_response(&bytes));
+        let (res, unpacked_size) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                if let Some(stats) = &self.stats {
+                    stats.record_error("decode");
+                }
+                return Err(e.context("parse query response"));
+            }
+        };
+        metrics.decode_time_ms = as_millis(start.elapsed());
+        metrics.response_bytes_uncompressed = unpacked_size as i64;
+
+        if let Some(endpoint) = endpoint {
+            if let Some(height) = res.archive_height {
+                self.transport.set_archive_height(endpoint, height);
+            }
+
+            if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+                if cache.is_cacheable(res.next_block, res.archive_height) {
+                    if let Err(e) = tokio::task::block_in_place(|| cache.put(key, &bytes)) {
+                        log::warn!("failed to write response cache: {}", auth::redact_error(&e));
+                    }
+                }
+            }
+        }
+        if let Some(stats) = &self.stats {
+            // the archive height of a cached response is stale
+            if let Some(height) = res.archive_height.filter(|_| endpoint.is_some()) {
+                stats.set_archive_height(height);
+            }
+            stats.set_next_block(res.next_block);
+        }
+
+        Ok((res, metrics))
+    }
+
+    /// Record the outcome of a request into the statistics of this client.
+    fn observe(&self, res: Result<&RequestMetrics, &anyhow::Error>) {
+        if let Some(stats) = &self.stats {
+            match res {
+                Ok(metrics) => stats.record_request(metrics),
+                Err(e) => stats.record_failed_request(&transport::error_code(e)),
+            }
+        }
+    }
+
+    /// Convert arrow data into a typed response and record the metrics of the request.
+    fn convert_typed(
+        &self,
+        res: QueryResponse,
+        mut metrics: RequestMetrics,
+    ) -> Result<QueryResponseTyped> {
+        let start = Instant::now();
+        let data = from_arrow::typed_data_from_arrow_data(res.data)
+            .context("convert arrow data to typed response")?;
+        metrics.decode_time_ms += as_millis(start.elapsed());
+
+        let start = Instant::now();
+        let mut resp: QueryResponseTyped = hyperfuel_client::QueryResponseTyped {
+            archive_height: res.archive_height,
+            next_block: res.next_block,
+            total_execution_time: res.total_execution_time,
+            data,
+        }
+        .into();
+        metrics.conversion_time_ms = as_millis(start.elapsed());
+
+        self.metrics.record(&metrics);
+        resp.metrics = metrics;
+
+        Ok(resp)
+    }
+}
+
+#[cfg(feature = "fault-injection")]
+#[napi]
+impl HyperfuelClient {
+    /// Create a new client that makes requests fail on purpose to test retries and failover.
+    ///
+    /// Only available in builds with the `fault-injection` feature, see `yarn build:test`.
+    #[napi]
+    pub fn new_with_fault_injection(
+        cfg: Config,
+        faults: config::FaultInjectionConfig,
+    ) -> napi::Result<HyperfuelClient> {
+        env_logger::try_init().ok();
+
+        let mut client = Self::new_impl(cfg).map_err(error::to_napi)?;
+        client
+            .transport
+            .inject_faults(&faults)
+            .context("configure fault injection")
+            .map_err(error::to_napi)?;
+
+        Ok(client)
+    }
+}
+
+// helper function to decode a list of "0x" prefixed or bare hex strings as addresses
+fn parse_addresses(addresses: Vec<String>) -> Result<Vec<Hash>> {
+    let mut parsed = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        // cut the "0x" off the address
+        let address: &str = address.strip_prefix("0x").unwrap_or(&address);
+        let address = hex_str_address_to_byte_array(address)
+            .context(format!("convert address {}", address))?;
+        parsed.push(address.into())
     }
+    Ok(parsed)
 }
 
 // helper function to decode hex string as address
@@ -0,0 +1,131 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use napi::{bindgen_prelude::ToNapiValue, sys};
+
+/// Client side measurements of a single request to the hyperfuel instance.
+#[napi(object)]
+#[derive(Debug, Default, Clone)]
+pub struct RequestMetrics {
+    /// Url of the instance that served the request, empty if it was served from the cache.
+    pub endpoint: String,
+    /// Whether the response was served from the local response cache.
+    pub cache_hit: bool,
+    /// Time spent waiting for the client side rate limit or concurrency cap in milliseconds.
+    pub rate_limit_wait_ms: f64,
+    /// Time spent resolving the host name in milliseconds.
+    /// Only set if a new connection had to be opened for this request.
+    pub dns_time_ms: Option<f64>,
+    /// Whether a new connection was opened for this request instead of reusing a pooled one.
+    pub new_connection: bool,
+    /// Time spent opening the tcp connection in milliseconds.
+    /// Only set if a new https connection was opened for this request, the end of the
+    /// connect isn't observable for plain http connections.
+    pub connect_time_ms: Option<f64>,
+    /// Time spent on the tls handshake in milliseconds.
+    /// Only set if a new https connection was opened for this request.
+    pub tls_handshake_time_ms: Option<f64>,
+    /// Time from sending the request until the response headers arrived in milliseconds.
+    /// This includes the dns, connect and tls handshake times if a new connection was opened.
+    pub time_to_first_byte_ms: f64,
+    /// Time spent reading the response body in milliseconds.
+    pub transfer_time_ms: f64,
+    /// Size of the response body as it was sent over the network (packed capnp).
+    pub response_bytes: i64,
+    /// Size of the response message after unpacking.
+    pub response_bytes_uncompressed: i64,
+    /// Time spent decoding the response into rust types in milliseconds.
+    pub decode_time_ms: f64,
+    /// Time spent converting the decoded response into the response types in milliseconds.
+    /// The conversion into javascript values happens after the response is handed over to
+    /// node, it is only counted in `napiConversionTimeMs` of the client metrics.
+    pub conversion_time_ms: f64,
+}
+
+/// Cumulative measurements of all requests made by a client.
+#[napi(object)]
+#[derive(Debug, Default, Clone)]
+pub struct ClientMetrics {
+    /// Number of requests made.
+    pub requests: i64,
+    /// Number of requests that had to open a new connection.
+    pub new_connections: i64,
+    /// Number of requests that were served from the local response cache.
+    pub cache_hits: i64,
+    pub rate_limit_wait_ms: f64,
+    pub dns_time_ms: f64,
+    pub connect_time_ms: f64,
+    pub tls_handshake_time_ms: f64,
+    pub time_to_first_byte_ms: f64,
+    pub transfer_time_ms: f64,
+    pub response_bytes: i64,
+    pub response_bytes_uncompressed: i64,
+    pub decode_time_ms: f64,
+    pub conversion_time_ms: f64,
+    /// Time spent converting responses into javascript values in milliseconds.
+    pub napi_conversion_time_ms: f64,
+}
+
+/// Accumulates request metrics for a client.
+#[derive(Default)]
+pub struct MetricsRecorder {
+    totals: Mutex<ClientMetrics>,
+}
+
+impl MetricsRecorder {
+    pub fn record(&self, m: &RequestMetrics) {
+        let mut totals = self.totals.lock().unwrap();
+        totals.requests += 1;
+        if m.new_connection {
+            totals.new_connections += 1;
+        }
+        if m.cache_hit {
+            totals.cache_hits += 1;
+        }
+        totals.rate_limit_wait_ms += m.rate_limit_wait_ms;
+        totals.dns_time_ms += m.dns_time_ms.unwrap_or(0.0);
+        totals.connect_time_ms += m.connect_time_ms.unwrap_or(0.0);
+        totals.tls_handshake_time_ms += m.tls_handshake_time_ms.unwrap_or(0.0);
+        totals.time_to_first_byte_ms += m.time_to_first_byte_ms;
+        totals.transfer_time_ms += m.transfer_time_ms;
+        totals.response_bytes += m.response_bytes;
+        totals.response_bytes_uncompressed += m.response_bytes_uncompressed;
+        totals.decode_time_ms += m.decode_time_ms;
+        totals.conversion_time_ms += m.conversion_time_ms;
+    }
+
+    pub fn record_napi_conversion(&self, d: Duration) {
+        self.totals.lock().unwrap().napi_conversion_time_ms += as_millis(d);
+    }
+
+    pub fn snapshot(&self) -> ClientMetrics {
+        self.totals.lock().unwrap().clone()
+    }
+}
+
+/// A response that records the time of its conversion into javascript values.
+pub struct Timed<T> {
+    value: T,
+    recorder: Arc<MetricsRecorder>,
+}
+
+impl<T> Timed<T> {
+    pub fn new(value: T, recorder: Arc<MetricsRecorder>) -> Self {
+        Self { value, recorder }
+    }
+}
+
+impl<T: ToNapiValue> ToNapiValue for Timed<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+        let start = Instant::now();
+        let res = T::to_napi_value(env, val.value);
+        val.recorder.record_napi_conversion(start.elapsed());
+        res
+    }
+}
+
+pub fn as_millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
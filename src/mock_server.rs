@@ -0,0 +1,446 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use anyhow::{anyhow, Context, Result};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use hyperfuel_net_types::{hyperfuel_net_types_capnp, ArchiveHeight, FieldSelection, Query};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::{
+    error,
+    to_arrow::{self, Cell, Row},
+    types::{Block, Input, Output, Receipt, Transaction},
+    util,
+};
+
+/// Data served by a `MockHyperfuelServer`, in the same shape `getData` returns it.
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct MockData {
+    pub blocks: Option<Vec<Block>>,
+    pub transactions: Option<Vec<Transaction>>,
+    pub receipts: Option<Vec<Receipt>>,
+    pub inputs: Option<Vec<Input>>,
+    pub outputs: Option<Vec<Output>>,
+}
+
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct MockServerOptions {
+    /// Height reported by the server, default is the highest block height in the data
+    pub archive_height: Option<i64>,
+    /// Maximum number of blocks to return in a single response, to force pagination
+    pub max_blocks_per_response: Option<i64>,
+    /// Maximum number of transactions to return in a single response, to force pagination
+    pub max_transactions_per_response: Option<i64>,
+    /// Requests without this bearer token are rejected with HTTP 401
+    pub bearer_token: Option<String>,
+}
+
+/// A local http server that implements the hyperfuel api on top of in-memory data.
+///
+/// Point `Config.url` at `url` to test code built on the client without network access.
+#[napi]
+pub struct MockHyperfuelServer {
+    url: String,
+    state: Arc<MockState>,
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+#[napi]
+impl MockHyperfuelServer {
+    /// Start serving the given data on a random local port.
+    #[napi]
+    pub async fn start(
+        data: MockData,
+        options: Option<MockServerOptions>,
+    ) -> napi::Result<MockHyperfuelServer> {
+        Self::start_impl(data, options.unwrap_or_default()).map_err(error::to_napi)
+    }
+
+    fn start_impl(data: MockData, options: MockServerOptions) -> Result<MockHyperfuelServer> {
+        let state = Arc::new(MockState::new(data, options).context("load mock data")?);
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(state.handle(req).await) }
+                }))
+            }
+        });
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = Server::try_bind(&addr)
+            .context("bind mock server")?
+            .serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        // the server also shuts down if this object is garbage collected and drops the sender
+        tokio::spawn(server.with_graceful_shutdown(async move {
+            shutdown_rx.await.ok();
+        }));
+
+        Ok(MockHyperfuelServer {
+            url,
+            state,
+            shutdown: Mutex::new(Some(shutdown)),
+        })
+    }
+
+    /// Url to put into `Config.url`
+    #[napi(getter)]
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Change the height reported by the server, e.g. to simulate a lagging instance.
+    /// Data above the height is not served.
+    #[napi]
+    pub fn set_archive_height(&self, height: Option<i64>) {
+        *self.state.archive_height.lock().unwrap() = height.map(|h| h as u64);
+    }
+
+    /// Number of requests the server received
+    #[napi]
+    pub fn request_count(&self) -> i64 {
+        self.state.requests.load(Ordering::Relaxed) as i64
+    }
+
+    /// Stop the server
+    #[napi]
+    pub fn close(&self) {
+        if let Some(shutdown) = self.shutdown.lock().unwrap().take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+struct MockState {
+    blocks: Vec<Row>,
+    transactions: Vec<Row>,
+    receipts: Vec<Row>,
+    inputs: Vec<Row>,
+    outputs: Vec<Row>,
+    archive_height: Mutex<Option<u64>>,
+    max_blocks: Option<usize>,
+    max_transactions: Option<usize>,
+    bearer_token: Option<String>,
+    requests: AtomicU64,
+}
+
+/// Selection of the query with the filter values decoded into cells.
+struct Selection(Vec<(String, Vec<Cell>)>);
+
+impl MockState {
+    fn new(data: MockData, options: MockServerOptions) -> Result<Self> {
+        fn rows<T>(
+            items: Option<Vec<T>>,
+            to_row: fn(&T) -> Result<Row>,
+            height_column: &str,
+        ) -> Result<Vec<Row>> {
+            let mut rows = items
+                .unwrap_or_default()
+                .iter()
+                .map(to_row)
+                .collect::<Result<Vec<_>>>()?;
+            rows.sort_by_key(|row| height(row, height_column));
+            Ok(rows)
+        }
+
+        let blocks = rows(data.blocks, to_arrow::block_row, "height").context("blocks")?;
+        let transactions = rows(data.transactions, to_arrow::transaction_row, "block_height")
+            .context("transactions")?;
+        let receipts =
+            rows(data.receipts, to_arrow::receipt_row, "block_height").context("receipts")?;
+        let inputs = rows(data.inputs, to_arrow::input_row, "block_height").context("inputs")?;
+        let outputs =
+            rows(data.outputs, to_arrow::output_row, "block_height").context("outputs")?;
+
+        let archive_height = match options.archive_height {
+            Some(height) => Some(height as u64),
+            None => blocks
+                .iter()
+                .map(|row| height(row, "height"))
+                .chain(
+                    [&transactions, &receipts, &inputs, &outputs]
+                        .into_iter()
+                        .flatten()
+                        .map(|row| height(row, "block_height")),
+                )
+                .max(),
+        };
+
+        let limit = |v: Option<i64>| v.map(|v| v.max(1) as usize);
+
+        Ok(Self {
+            blocks,
+            transactions,
+            receipts,
+            inputs,
+            outputs,
+            archive_height: Mutex::new(archive_height),
+            max_blocks: limit(options.max_blocks_per_response),
+            max_transactions: limit(options.max_transactions_per_response),
+            bearer_token: options.bearer_token,
+            requests: AtomicU64::new(0),
+        })
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(token) = &self.bearer_token {
+            let expected = format!("Bearer {}", token);
+            let authorized = req
+                .headers()
+                .get(hyper::header::AUTHORIZATION)
+                .is_some_and(|v| v.as_bytes() == expected.as_bytes());
+            if !authorized {
+                return text_response(StatusCode::UNAUTHORIZED, "unauthorized".to_owned());
+            }
+        }
+
+        let path = req.uri().path().trim_end_matches('/').to_owned();
+        let res = match (req.method(), path.as_str()) {
+            (&Method::GET, p) if p.ends_with("/height") => self.height(),
+            (&Method::POST, p) if p.ends_with("/query/arrow-ipc") => {
+                match hyper::body::to_bytes(req.into_body()).await {
+                    Ok(body) => self.query(&body),
+                    Err(e) => Err(anyhow!("read request body: {}", e)),
+                }
+            }
+            _ => return text_response(StatusCode::NOT_FOUND, "not found".to_owned()),
+        };
+
+        match res {
+            Ok(res) => res,
+            Err(e) => text_response(StatusCode::BAD_REQUEST, format!("{:?}", e)),
+        }
+    }
+
+    fn height(&self) -> Result<Response<Body>> {
+        let height = ArchiveHeight {
+            height: *self.archive_height.lock().unwrap(),
+        };
+        let body = serde_json::to_vec(&height).context("serialize height")?;
+
+        Ok(Response::new(Body::from(body)))
+    }
+
+    fn query(&self, body: &[u8]) -> Result<Response<Body>> {
+        let start = Instant::now();
+        let query: Query = serde_json::from_slice(body).context("parse query")?;
+
+        let archive_height = *self.archive_height.lock().unwrap();
+        let upper = match archive_height {
+            Some(height) => query
+                .to_block
+                .unwrap_or(u64::MAX)
+                .min(height.saturating_add(1)),
+            None => query.from_block,
+        };
+        let upper = upper.max(query.from_block);
+        let in_range = |h: u64| h >= query.from_block && h < upper;
+
+        // transactions that have a receipt, input or output matching the selections
+        let mut selected: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+        for (rows, selections) in [
+            (&self.receipts, selections(&query.receipts)?),
+            (&self.inputs, selections(&query.inputs)?),
+            (&self.outputs, selections(&query.outputs)?),
+        ] {
+            for row in rows.iter() {
+                let h = height(row, "block_height");
+                if in_range(h) && selections.iter().any(|s| s.matches(row)) {
+                    if let Some(tx_id) = row.get("tx_id").and_then(Cell::as_bytes) {
+                        selected.insert(tx_id.to_vec(), h);
+                    }
+                }
+            }
+        }
+
+        // cut the page short after the configured number of blocks or transactions
+        let mut per_height: BTreeMap<u64, usize> = BTreeMap::new();
+        for &h in selected.values() {
+            *per_height.entry(h).or_default() += 1;
+        }
+        if query.include_all_blocks {
+            for row in self.blocks.iter() {
+                let h = height(row, "height");
+                if in_range(h) {
+                    per_height.entry(h).or_default();
+                }
+            }
+        }
+        let max_blocks = min_limit(self.max_blocks, query.max_num_blocks);
+        let max_transactions = min_limit(self.max_transactions, query.max_num_transactions);
+        let mut next_block = upper;
+        let (mut num_blocks, mut num_transactions) = (0, 0);
+        for (&h, &num_txs) in per_height.iter() {
+            num_blocks += 1;
+            num_transactions += num_txs;
+            let full = max_blocks.is_some_and(|max| num_blocks >= max)
+                || max_transactions.is_some_and(|max| num_transactions >= max);
+            if full {
+                next_block = (h + 1).min(upper);
+                break;
+            }
+        }
+        selected.retain(|_, h| *h < next_block);
+
+        let selected_heights: BTreeSet<u64> = selected.values().copied().collect();
+        let blocks: Vec<&Row> = self
+            .blocks
+            .iter()
+            .filter(|row| {
+                let h = height(row, "height");
+                h >= query.from_block
+                    && h < next_block
+                    && (query.include_all_blocks || selected_heights.contains(&h))
+            })
+            .collect();
+        fn related<'a>(
+            rows: &'a [Row],
+            id_column: &str,
+            selected: &BTreeMap<Vec<u8>, u64>,
+        ) -> Vec<&'a Row> {
+            rows.iter()
+                .filter(|row| {
+                    row.get(id_column)
+                        .and_then(Cell::as_bytes)
+                        .is_some_and(|id| selected.contains_key(id))
+                })
+                .collect()
+        }
+        let transactions = related(&self.transactions, "id", &selected);
+        let receipts = related(&self.receipts, "tx_id", &selected);
+        let inputs = related(&self.inputs, "tx_id", &selected);
+        let outputs = related(&self.outputs, "tx_id", &selected);
+
+        let FieldSelection {
+            block,
+            transaction,
+            receipt,
+            input,
+            output,
+        } = &query.field_selection;
+        let blocks = to_arrow::rows_to_ipc(&hyperfuel_schema::block_header(), block, &blocks)
+            .context("encode blocks")?;
+        let transactions =
+            to_arrow::rows_to_ipc(&hyperfuel_schema::transaction(), transaction, &transactions)
+                .context("encode transactions")?;
+        let receipts = to_arrow::rows_to_ipc(&hyperfuel_schema::receipt(), receipt, &receipts)
+            .context("encode receipts")?;
+        let inputs = to_arrow::rows_to_ipc(&hyperfuel_schema::input(), input, &inputs)
+            .context("encode inputs")?;
+        let outputs = to_arrow::rows_to_ipc(&hyperfuel_schema::output(), output, &outputs)
+            .context("encode outputs")?;
+
+        let mut message = capnp::message::Builder::new_default();
+        let mut response =
+            message.init_root::<hyperfuel_net_types_capnp::query_response::Builder>();
+        response.set_archive_height(archive_height.map(|h| h as i64).unwrap_or(-1));
+        response.set_next_block(next_block);
+        response.set_total_execution_time(start.elapsed().as_millis() as u64);
+        let mut data = response.init_data();
+        data.set_blocks(&blocks);
+        data.set_transactions(&transactions);
+        data.set_receipts(&receipts);
+        data.set_inputs(&inputs);
+        data.set_outputs(&outputs);
+
+        let mut body = Vec::new();
+        capnp::serialize_packed::write_message(&mut body, &message)
+            .context("write response message")?;
+
+        Ok(Response::new(Body::from(body)))
+    }
+}
+
+impl Selection {
+    /// A row matches if every non-empty filter of the selection contains the value of its column.
+    fn matches(&self, row: &Row) -> bool {
+        self.0.iter().all(|(column, values)| {
+            row.get(column)
+                .is_some_and(|cell| values.iter().any(|v| cell_eq(v, cell)))
+        })
+    }
+}
+
+fn selections<S: Serialize>(selections: &[S]) -> Result<Vec<Selection>> {
+    selections
+        .iter()
+        .map(|selection| {
+            let value = serde_json::to_value(selection).context("serialize selection")?;
+            let Value::Object(fields) = value else {
+                return Err(anyhow!("selection is not an object"));
+            };
+
+            let mut filters = Vec::new();
+            for (column, values) in fields {
+                let Value::Array(values) = values else {
+                    continue;
+                };
+                if values.is_empty() {
+                    continue;
+                }
+                let values = values
+                    .iter()
+                    .map(|v| match v {
+                        Value::Number(n) => n
+                            .as_u64()
+                            .map(Cell::UInt)
+                            .context("filter value is not an unsigned integer"),
+                        Value::String(s) => util::decode_hex(s).map(Cell::Binary),
+                        v => Err(anyhow!("unexpected filter value {}", v)),
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| format!("decode {} filter", column))?;
+                filters.push((column, values));
+            }
+
+            Ok(Selection(filters))
+        })
+        .collect()
+}
+
+fn cell_eq(filter: &Cell, cell: &Cell) -> bool {
+    match (filter, cell) {
+        (Cell::Binary(a), Cell::Binary(b)) => a == b,
+        (a, b) => a.as_u64().is_some() && a.as_u64() == b.as_u64(),
+    }
+}
+
+fn height(row: &Row, column: &str) -> u64 {
+    row.get(column).and_then(Cell::as_u64).unwrap_or(0)
+}
+
+fn min_limit(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn text_response(status: StatusCode, body: String) -> Response<Body> {
+    let mut res = Response::new(Body::from(body));
+    *res.status_mut() = status;
+    res
+}
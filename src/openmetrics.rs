@@ -0,0 +1,249 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::metrics::RequestMetrics;
+
+/// Upper bounds of the request latency histogram buckets in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<ClientStats>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<BTreeMap<String, Arc<ClientStats>>> {
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Get the statistics registered under the given label, registering them if needed.
+///
+/// Clients that share a label share their statistics.
+pub fn register(label: &str) -> Arc<ClientStats> {
+    registry()
+        .lock()
+        .unwrap()
+        .entry(label.to_owned())
+        .or_default()
+        .clone()
+}
+
+/// Statistics of the clients registered under a single label.
+#[derive(Default)]
+pub struct ClientStats {
+    inner: Mutex<Stats>,
+}
+
+#[derive(Default)]
+struct Stats {
+    requests: u64,
+    errors: BTreeMap<String, u64>,
+    retries: u64,
+    response_bytes: u64,
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+    latency_count: u64,
+    archive_height: Option<u64>,
+    next_block: Option<u64>,
+}
+
+impl ClientStats {
+    pub fn record_request(&self, m: &RequestMetrics) {
+        let mut stats = self.inner.lock().unwrap();
+        stats.requests += 1;
+        stats.response_bytes += m.response_bytes as u64;
+
+        let latency = (m.time_to_first_byte_ms + m.transfer_time_ms) / 1000.0;
+        for (bucket, &bound) in stats.latency_buckets.iter_mut().zip(LATENCY_BUCKETS.iter()) {
+            if latency <= bound {
+                *bucket += 1;
+            }
+        }
+        stats.latency_sum += latency;
+        stats.latency_count += 1;
+    }
+
+    pub fn record_failed_request(&self, code: &str) {
+        let mut stats = self.inner.lock().unwrap();
+        stats.requests += 1;
+        *stats.errors.entry(code.to_owned()).or_default() += 1;
+    }
+
+    /// Record an error that didn't fail the request, because the request was retried or the
+    /// error happened after the request itself succeeded.
+    pub fn record_error(&self, code: &str) {
+        let mut stats = self.inner.lock().unwrap();
+        *stats.errors.entry(code.to_owned()).or_default() += 1;
+    }
+
+    pub fn record_retry(&self) {
+        self.inner.lock().unwrap().retries += 1;
+    }
+
+    pub fn set_archive_height(&self, height: u64) {
+        self.inner.lock().unwrap().archive_height = Some(height);
+    }
+
+    pub fn set_next_block(&self, next_block: u64) {
+        self.inner.lock().unwrap().next_block = Some(next_block);
+    }
+}
+
+/// Render the statistics of all registered clients in OpenMetrics text format.
+pub fn render() -> String {
+    let clients = registry().lock().unwrap();
+    let clients: Vec<(String, std::sync::MutexGuard<Stats>)> = clients
+        .iter()
+        .map(|(label, stats)| (escape_label(label), stats.inner.lock().unwrap()))
+        .collect();
+
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "hyperfuel_client_requests",
+        "counter",
+        "Number of requests sent to the hyperfuel instance.",
+    );
+    for (label, stats) in clients.iter() {
+        writeln!(
+            out,
+            "hyperfuel_client_requests_total{{client=\"{}\"}} {}",
+            label, stats.requests
+        )
+        .unwrap();
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_request_errors",
+        "counter",
+        "Number of failed requests by error code.",
+    );
+    for (label, stats) in clients.iter() {
+        for (code, count) in stats.errors.iter() {
+            writeln!(
+                out,
+                "hyperfuel_client_request_errors_total{{client=\"{}\",code=\"{}\"}} {}",
+                label,
+                escape_label(code),
+                count
+            )
+            .unwrap();
+        }
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_retries",
+        "counter",
+        "Number of requests that were retried.",
+    );
+    for (label, stats) in clients.iter() {
+        writeln!(
+            out,
+            "hyperfuel_client_retries_total{{client=\"{}\"}} {}",
+            label, stats.retries
+        )
+        .unwrap();
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_response_bytes",
+        "counter",
+        "Number of response body bytes received over the network.",
+    );
+    for (label, stats) in clients.iter() {
+        writeln!(
+            out,
+            "hyperfuel_client_response_bytes_total{{client=\"{}\"}} {}",
+            label, stats.response_bytes
+        )
+        .unwrap();
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_request_duration_seconds",
+        "histogram",
+        "Time from sending a request until its response body was read.",
+    );
+    for (label, stats) in clients.iter() {
+        for (&bound, &count) in LATENCY_BUCKETS.iter().zip(stats.latency_buckets.iter()) {
+            writeln!(
+                out,
+                "hyperfuel_client_request_duration_seconds_bucket{{client=\"{}\",le=\"{}\"}} {}",
+                label, bound, count
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "hyperfuel_client_request_duration_seconds_bucket{{client=\"{}\",le=\"+Inf\"}} {}",
+            label, stats.latency_count
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "hyperfuel_client_request_duration_seconds_sum{{client=\"{}\"}} {}",
+            label, stats.latency_sum
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "hyperfuel_client_request_duration_seconds_count{{client=\"{}\"}} {}",
+            label, stats.latency_count
+        )
+        .unwrap();
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_archive_height",
+        "gauge",
+        "Last archive height reported by the hyperfuel instance.",
+    );
+    for (label, stats) in clients.iter() {
+        if let Some(height) = stats.archive_height {
+            writeln!(
+                out,
+                "hyperfuel_client_archive_height{{client=\"{}\"}} {}",
+                label, height
+            )
+            .unwrap();
+        }
+    }
+
+    family(
+        &mut out,
+        "hyperfuel_client_next_block",
+        "gauge",
+        "Next block to query for according to the last response.",
+    );
+    for (label, stats) in clients.iter() {
+        if let Some(next_block) = stats.next_block {
+            writeln!(
+                out,
+                "hyperfuel_client_next_block{{client=\"{}\"}} {}",
+                label, next_block
+            )
+            .unwrap();
+        }
+    }
+
+    out.push_str("# EOF\n");
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
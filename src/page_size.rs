@@ -0,0 +1,224 @@
+use std::{collections::HashSet, hash::Hash, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use arrow2::array::{BinaryArray, UInt64Array};
+use hyperfuel_client::{ArrowBatch, QueryResponseData};
+use hyperfuel_net_types::Query;
+
+use crate::{config::Config, pages::Page};
+
+/// Weight of the latest page in the moving averages of the page density
+const SMOOTHING: f64 = 0.5;
+/// Share of the observed server time limit that a page should take
+const TIME_LIMIT_HEADROOM: f64 = 0.8;
+
+/// Size that the pages of paginated operations should land near.
+#[derive(Debug, Clone, Copy)]
+pub struct PageTarget {
+    rows: Option<u64>,
+    bytes: Option<u64>,
+}
+
+impl PageTarget {
+    /// Read the target configured in `cfg`, returns `None` if no target is set.
+    pub fn from_config(cfg: &Config) -> Result<Option<Self>> {
+        let positive = |value: Option<i64>, name: &str| {
+            value
+                .map(|v| {
+                    u64::try_from(v)
+                        .ok()
+                        .filter(|&v| v > 0)
+                        .ok_or_else(|| anyhow!("{} has to be positive", name))
+                })
+                .transpose()
+        };
+        let rows = positive(cfg.target_page_rows, "target_page_rows")?;
+        let bytes = positive(cfg.target_page_bytes, "target_page_bytes")?;
+
+        if rows.is_none() && bytes.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { rows, bytes }))
+    }
+}
+
+/// Adapts the block range and the limits of the requests of a paginated operation,
+/// so each page lands near the target size and within the time limit of the server.
+///
+/// The first request is sent as is, later ones are sized from the density of the pages
+/// fetched so far.
+pub struct PageSizer {
+    target: PageTarget,
+    estimate: Mutex<Estimate>,
+}
+
+#[derive(Default)]
+struct Estimate {
+    rows_per_block: Option<f64>,
+    bytes_per_block: Option<f64>,
+    /// Share of the rows that are transactions
+    transactions_per_row: Option<f64>,
+    blocks_per_ms: Option<f64>,
+    /// Execution time of the last page that the server cut short
+    time_limit_ms: Option<u64>,
+}
+
+impl PageSizer {
+    pub fn new(target: PageTarget) -> Self {
+        Self {
+            target,
+            estimate: Mutex::default(),
+        }
+    }
+
+    /// Build the request for the page starting at `from_block`.
+    ///
+    /// Narrows the range of `base` and lowers its `max_num_blocks` and `max_num_transactions`,
+    /// it never asks for more than `base` or goes past `to_block`.
+    pub fn next_query(&self, base: &Query, from_block: u64, to_block: u64) -> Query {
+        let mut query = base.clone();
+        query.from_block = from_block;
+
+        let estimate = self.estimate.lock().unwrap();
+
+        let mut blocks = f64::INFINITY;
+        if let (Some(target), Some(density)) = (self.target.rows, estimate.rows_per_block) {
+            blocks = blocks.min(target as f64 / density);
+        }
+        if let (Some(target), Some(density)) = (self.target.bytes, estimate.bytes_per_block) {
+            blocks = blocks.min(target as f64 / density);
+        }
+        if let (Some(limit), Some(speed)) = (estimate.time_limit_ms, estimate.blocks_per_ms) {
+            blocks = blocks.min(limit as f64 * speed * TIME_LIMIT_HEADROOM);
+        }
+        if !blocks.is_finite() {
+            return query;
+        }
+        let blocks = (blocks as u64).max(1);
+
+        query.to_block = Some(from_block.saturating_add(blocks).min(to_block));
+        query.max_num_blocks = Some(lower(base.max_num_blocks, blocks as usize));
+
+        let transactions = self
+            .target
+            .rows
+            .zip(estimate.transactions_per_row)
+            .map(|(rows, share)| (rows as f64 * share).ceil() as usize);
+        if let Some(transactions) = transactions {
+            query.max_num_transactions =
+                Some(lower(base.max_num_transactions, transactions.max(1)));
+        }
+
+        query
+    }
+
+    /// Update the density estimates with a page that was returned for `query`.
+    pub fn observe(&self, query: &Query, page: &Page) {
+        let (res, metrics) = page;
+        let data = &res.data;
+
+        let covered = res.next_block.saturating_sub(query.from_block);
+        let blocks = covered.max(1) as f64;
+        let rows = num_rows(&data.blocks)
+            + num_rows(&data.transactions)
+            + num_rows(&data.receipts)
+            + num_rows(&data.inputs)
+            + num_rows(&data.outputs);
+        // an empty page has no blocks or transactions, even if their ids aren't selected
+        let empty = (rows == 0).then_some(0);
+        let num_blocks = num_blocks(data).or(empty);
+        let num_transactions = num_transactions(data).or(empty);
+
+        let mut estimate = self.estimate.lock().unwrap();
+
+        smooth(&mut estimate.rows_per_block, rows as f64 / blocks);
+        smooth(
+            &mut estimate.bytes_per_block,
+            metrics.response_bytes as f64 / blocks,
+        );
+        if let Some(transactions) = num_transactions.filter(|_| rows > 0) {
+            smooth(
+                &mut estimate.transactions_per_row,
+                transactions as f64 / rows as f64,
+            );
+        }
+        if res.total_execution_time > 0 {
+            smooth(
+                &mut estimate.blocks_per_ms,
+                blocks / res.total_execution_time as f64,
+            );
+        }
+
+        // a page that ends before the requested range without hitting any of the limits
+        // was cut short by the time limit of the server. Limits that can't be checked because
+        // the ids aren't selected are assumed to be hit, unless the page spans fewer blocks
+        // than the block limit.
+        let range_end = query.to_block.unwrap_or(u64::MAX);
+        let hit_limit = query.max_num_blocks.is_some_and(|max| {
+            num_blocks.map_or(covered >= max as u64, |num_blocks| num_blocks >= max)
+        }) || query
+            .max_num_transactions
+            .is_some_and(|max| num_transactions.is_none_or(|num_txs| num_txs >= max));
+        if res.next_block < range_end && !hit_limit && res.total_execution_time > 0 {
+            estimate.time_limit_ms = Some(res.total_execution_time);
+        }
+    }
+}
+
+fn num_rows(batches: &[ArrowBatch]) -> usize {
+    batches.iter().map(|batch| batch.chunk.len()).sum()
+}
+
+/// Number of distinct blocks in the page, `None` if no table has the block height selected.
+fn num_blocks(data: &QueryResponseData) -> Option<usize> {
+    let tables = [
+        (&data.blocks, "height"),
+        (&data.transactions, "block_height"),
+        (&data.receipts, "block_height"),
+        (&data.inputs, "block_height"),
+        (&data.outputs, "block_height"),
+    ];
+    count_distinct::<UInt64Array, _>(&tables, |col| col.iter().flatten().copied().collect())
+}
+
+/// Number of distinct transactions in the page, `None` if no table has the transaction id
+/// selected.
+fn num_transactions(data: &QueryResponseData) -> Option<usize> {
+    let tables = [
+        (&data.transactions, "id"),
+        (&data.receipts, "tx_id"),
+        (&data.inputs, "tx_id"),
+        (&data.outputs, "tx_id"),
+    ];
+    count_distinct::<BinaryArray<i32>, _>(&tables, |col| col.iter().flatten().collect())
+}
+
+fn count_distinct<'a, A: 'static, T: Eq + Hash>(
+    tables: &[(&'a Vec<ArrowBatch>, &str)],
+    values: impl Fn(&'a A) -> Vec<T>,
+) -> Option<usize> {
+    let mut distinct = HashSet::new();
+    let mut selected = false;
+    for (batches, column) in tables {
+        for batch in batches.iter() {
+            if let Ok(col) = batch.column::<A>(column) {
+                selected = true;
+                distinct.extend(values(col));
+            }
+        }
+    }
+
+    selected.then_some(distinct.len())
+}
+
+fn smooth(average: &mut Option<f64>, value: f64) {
+    *average = Some(match *average {
+        Some(average) => average * (1.0 - SMOOTHING) + value * SMOOTHING,
+        None => value,
+    });
+}
+
+fn lower(limit: Option<usize>, value: usize) -> usize {
+    limit.map_or(value, |limit| limit.min(value))
+}
@@ -0,0 +1,239 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use futures::{stream, Stream, StreamExt};
+use hyperfuel_client::QueryResponse;
+use hyperfuel_net_types::Query;
+
+use crate::{
+    metrics::RequestMetrics, page_size::PageSizer, query::ParallelOptions, trace::Span,
+    HyperfuelClient,
+};
+
+/// A decoded response page along with the metrics of the request that fetched it.
+pub type Page = (QueryResponse, RequestMetrics);
+
+const DEFAULT_CONCURRENCY: i64 = 4;
+/// Number of chunks per worker the range is split into before anything is known about it
+const INITIAL_CHUNKS_PER_WORKER: u64 = 4;
+const MIN_CHUNK_BLOCKS: u64 = 100;
+/// Server execution time that a chunk should take once the density of the range is known
+const TARGET_CHUNK_EXECUTION_MS: u64 = 2_000;
+/// Number of pages a chunk should take if the server doesn't report its execution time
+const TARGET_CHUNK_PAGES: u64 = 4;
+
+/// End of a range that should reach `to_block`, or the head of the chain if it isn't given.
+///
+/// `to_block` is exclusive, so the range is capped right after the head block at `height`.
+pub fn cap_to_head(to_block: Option<u64>, height: u64) -> u64 {
+    let end = height + 1;
+    to_block.map_or(end, |to_block| to_block.min(end))
+}
+
+/// Fetch the pages of the query one after another until `to_block` is reached.
+///
+/// Requests are sized to the page target of the client if it has one.
+/// The stream is empty if the range is, and ends after the first error.
+pub fn sequential<'a>(
+    client: &'a HyperfuelClient,
+    query: Query,
+    to_block: u64,
+    span: &'a Span,
+) -> impl Stream<Item = Result<Page>> + 'a {
+    let sizer = client
+        .page_target
+        .map(|target| Arc::new(PageSizer::new(target)));
+    let from_block = query.from_block;
+    let query = Arc::new(query);
+
+    let start = Some(from_block).filter(|&from_block| from_block < to_block);
+
+    stream::unfold(start, move |from_block| {
+        let (query, sizer) = (query.clone(), sizer.clone());
+        async move {
+            let from_block = from_block?;
+            let req = next_query(&query, sizer.as_deref(), from_block, to_block);
+            let page = match client.get_arrow_data_with_retry(&req, span).await {
+                Ok(page) => page,
+                Err(e) => return Some((Err(e), None)),
+            };
+            if let Err(e) = check_progress(from_block, &page) {
+                return Some((Err(e), None));
+            }
+            if let Some(sizer) = &sizer {
+                sizer.observe(&req, &page);
+            }
+
+            let next = Some(page.0.next_block).filter(|&next| next < to_block);
+
+            Some((Ok(page), next))
+        }
+    })
+}
+
+/// Split `[query.from_block, to_block)` into chunks and fetch them in parallel.
+///
+/// Pages are yielded in block order, so at most `concurrency` chunks are held in memory.
+/// Chunks are sized from the progress and execution time of the pages fetched so far.
+/// A chunk that failed yields its error in place of its pages.
+pub fn parallel<'a>(
+    client: &'a HyperfuelClient,
+    query: Query,
+    to_block: u64,
+    options: &ParallelOptions,
+    span: &'a Span,
+) -> Result<impl Stream<Item = Result<Page>> + 'a> {
+    let concurrency = usize::try_from(options.concurrency.unwrap_or(DEFAULT_CONCURRENCY))
+        .ok()
+        .filter(|&c| c > 0)
+        .ok_or_else(|| anyhow!("concurrency has to be positive"))?;
+    let initial_chunk_blocks = match options.initial_chunk_blocks {
+        Some(blocks) => u64::try_from(blocks)
+            .ok()
+            .filter(|&b| b > 0)
+            .ok_or_else(|| anyhow!("initial_chunk_blocks has to be positive"))?,
+        None => {
+            to_block.saturating_sub(query.from_block)
+                / (concurrency as u64 * INITIAL_CHUNKS_PER_WORKER)
+        }
+    };
+
+    let page_sizer = client
+        .page_target
+        .map(|target| Arc::new(PageSizer::new(target)));
+    let sizer = Arc::new(Mutex::new(ChunkSizer {
+        initial: initial_chunk_blocks.max(MIN_CHUNK_BLOCKS),
+        workers: concurrency as u64,
+        ..Default::default()
+    }));
+
+    let ranges = {
+        let sizer = sizer.clone();
+        stream::unfold(query.from_block, move |start| {
+            let sizer = sizer.clone();
+            async move {
+                if start >= to_block {
+                    return None;
+                }
+                let size = sizer.lock().unwrap().next_size(to_block - start);
+                let end = start.saturating_add(size).min(to_block);
+                Some(((start, end), end))
+            }
+        })
+    };
+
+    let pages = ranges
+        .map(move |(start, end)| {
+            let mut query = query.clone();
+            query.from_block = start;
+            query.to_block = Some(end);
+            fetch_chunk(client, query, end, sizer.clone(), page_sizer.clone(), span)
+        })
+        .buffered(concurrency)
+        .flat_map(|chunk| match chunk {
+            Ok(pages) => stream::iter(pages.into_iter().map(Ok).collect::<Vec<_>>()),
+            Err(e) => stream::iter(vec![Err(e)]),
+        });
+
+    Ok(pages)
+}
+
+async fn fetch_chunk(
+    client: &HyperfuelClient,
+    query: Query,
+    end: u64,
+    sizer: Arc<Mutex<ChunkSizer>>,
+    page_sizer: Option<Arc<PageSizer>>,
+    parent: &Span,
+) -> Result<Vec<Page>> {
+    let mut span = parent.child("chunk");
+    span.set("from_block", query.from_block);
+    span.set("to_block", end);
+
+    let mut pages = Vec::new();
+    let mut from_block = query.from_block;
+    loop {
+        let req = next_query(&query, page_sizer.as_deref(), from_block, end);
+        let page = client.get_arrow_data_with_retry(&req, &span).await?;
+        check_progress(from_block, &page)?;
+        let next_block = page.0.next_block;
+
+        sizer.lock().unwrap().observe(
+            next_block.saturating_sub(from_block),
+            page.0.total_execution_time,
+        );
+        if let Some(page_sizer) = &page_sizer {
+            page_sizer.observe(&req, &page);
+        }
+        pages.push(page);
+
+        if next_block >= end {
+            break;
+        }
+        from_block = next_block;
+    }
+
+    span.set("pages", pages.len());
+
+    Ok(pages)
+}
+
+/// Fail if the server didn't move past the start of the request, requesting the next page
+/// would then fetch the same page forever.
+fn check_progress(from_block: u64, page: &Page) -> Result<()> {
+    let next_block = page.0.next_block;
+    if next_block <= from_block {
+        return Err(anyhow!(
+            "server returned next_block {} for a request from block {}",
+            next_block,
+            from_block
+        ));
+    }
+    Ok(())
+}
+
+fn next_query(base: &Query, sizer: Option<&PageSizer>, from_block: u64, to_block: u64) -> Query {
+    match sizer {
+        Some(sizer) => sizer.next_query(base, from_block, to_block),
+        None => {
+            let mut query = base.clone();
+            query.from_block = from_block;
+            query
+        }
+    }
+}
+
+/// Picks the size of the next chunk based on the pages fetched so far.
+#[derive(Default)]
+struct ChunkSizer {
+    initial: u64,
+    workers: u64,
+    pages: u64,
+    blocks: u64,
+    execution_ms: u64,
+}
+
+impl ChunkSizer {
+    fn observe(&mut self, blocks: u64, execution_ms: u64) {
+        self.pages += 1;
+        self.blocks += blocks;
+        self.execution_ms += execution_ms;
+    }
+
+    fn next_size(&self, remaining: u64) -> u64 {
+        let per_page = self.blocks.checked_div(self.pages);
+        let size = match per_page {
+            None => self.initial,
+            Some(per_page) => self
+                .blocks
+                .saturating_mul(TARGET_CHUNK_EXECUTION_MS)
+                .checked_div(self.execution_ms)
+                .unwrap_or(per_page * TARGET_CHUNK_PAGES),
+        };
+
+        // keep every worker busy until the end of the range
+        let max = remaining.div_ceil(self.workers).max(MIN_CHUNK_BLOCKS);
+
+        size.clamp(MIN_CHUNK_BLOCKS, max)
+    }
+}
@@ -0,0 +1,201 @@
+// Adapted from `src/parquet_out.rs` of the hyperfuel-client crate, version 2.2.1, which is
+// licensed under the MIT license. The request loop is replaced by the pagination of pages.rs,
+// writing the files is unchanged.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use arrow2::{
+    datatypes::Schema,
+    io::parquet::write::{transverse, Encoding, FileSink, WriteOptions},
+};
+use futures::{SinkExt, StreamExt};
+use hyperfuel_net_types::Query;
+use hyperfuel_schema::project_schema;
+use tokio::fs::File;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::{pages, query::ParallelOptions, trace::Span, HyperfuelClient};
+
+/// Create a parquet file by executing a query.
+///
+/// If the query can't be finished in a single request, this function will
+/// keep on making requests using the pagination mechanism (next_block) until
+/// it reaches the end. It will stream data into the parquet file as it comes from
+/// the server.
+///
+/// With `parallel` options the range is split into sub-ranges that are fetched in parallel,
+/// the data is still written in block order.
+pub async fn create_parquet_folder(
+    client: &HyperfuelClient,
+    query: Query,
+    path: String,
+    parallel: Option<&ParallelOptions>,
+    span: &Span,
+) -> Result<()> {
+    let height = client.get_height_with_retry_impl().await?;
+
+    let to_block = pages::cap_to_head(query.to_block, height);
+
+    let mut path = PathBuf::from(path);
+
+    tokio::fs::create_dir_all(&path)
+        .await
+        .context("create parquet dir")?;
+
+    path.push("block.parquet");
+    let mut blocks = make_file_sink(
+        &path,
+        &hyperfuel_schema::block_header(),
+        &query.field_selection.block,
+    )
+    .await
+    .context("create blocks output parquet")?;
+    path.pop();
+
+    path.push("transaction.parquet");
+    let mut txs = make_file_sink(
+        &path,
+        &hyperfuel_schema::transaction(),
+        &query.field_selection.transaction,
+    )
+    .await
+    .context("create transactions output parquet")?;
+    path.pop();
+
+    path.push("receipt.parquet");
+    let mut receipts = make_file_sink(
+        &path,
+        &hyperfuel_schema::receipt(),
+        &query.field_selection.receipt,
+    )
+    .await
+    .context("create receipts output parquet")?;
+    path.pop();
+
+    path.push("input.parquet");
+    let mut inputs = make_file_sink(
+        &path,
+        &hyperfuel_schema::input(),
+        &query.field_selection.input,
+    )
+    .await
+    .context("create inputs output parquet")?;
+    path.pop();
+
+    path.push("output.parquet");
+    let mut outputs = make_file_sink(
+        &path,
+        &hyperfuel_schema::output(),
+        &query.field_selection.output,
+    )
+    .await
+    .context("create outputs output parquet")?;
+    path.pop();
+
+    let mut pages = match parallel {
+        Some(options) => pages::parallel(client, query, to_block, options, span)
+            .context("split query range")?
+            .boxed(),
+        None => pages::sequential(client, query, to_block, span).boxed(),
+    };
+
+    while let Some(page) = pages.next().await {
+        let (resp, metrics) = page?;
+        client.metrics.record(&metrics);
+
+        for batch in resp.data.blocks {
+            blocks
+                .send(batch.chunk)
+                .await
+                .context("write blocks chunk to parquet")?;
+        }
+
+        for batch in resp.data.transactions {
+            txs.send(batch.chunk)
+                .await
+                .context("write transactions chunk to parquet")?;
+        }
+
+        for batch in resp.data.receipts {
+            receipts
+                .send(batch.chunk)
+                .await
+                .context("write receipts chunk to parquet")?;
+        }
+
+        for batch in resp.data.inputs {
+            inputs
+                .send(batch.chunk)
+                .await
+                .context("write inputs chunk to parquet")?;
+        }
+
+        for batch in resp.data.outputs {
+            outputs
+                .send(batch.chunk)
+                .await
+                .context("write outputs chunk to parquet")?;
+        }
+    }
+    std::mem::drop(pages);
+
+    blocks
+        .close()
+        .await
+        .context("finish writing blocks parquet")?;
+    txs.close()
+        .await
+        .context("finish writing transactions parquet")?;
+    receipts
+        .close()
+        .await
+        .context("finish writing receipts parquet")?;
+    inputs
+        .close()
+        .await
+        .context("finish writing inputs parquet")?;
+    outputs
+        .close()
+        .await
+        .context("finish writing outputs parquet")?;
+
+    Ok(())
+}
+
+async fn make_file_sink(
+    path: &Path,
+    schema: &Schema,
+    field_selection: &BTreeSet<String>,
+) -> Result<FileSink<'static, Compat<File>>> {
+    let file = tokio::fs::File::create(path)
+        .await
+        .context("create parquet file")?
+        .compat_write();
+
+    let schema = project_schema(schema, field_selection).context("project schema")?;
+
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|f| transverse(&f.data_type, |_| Encoding::Plain))
+        .collect();
+
+    let file_sink = FileSink::try_new(
+        file,
+        schema,
+        encodings,
+        WriteOptions {
+            write_statistics: true,
+            version: arrow2::io::parquet::write::Version::V2,
+            compression: arrow2::io::parquet::write::CompressionOptions::Lz4Raw,
+            data_pagesize_limit: None,
+        },
+    )
+    .context("create file sink")?;
+
+    Ok(file_sink)
+}
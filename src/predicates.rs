@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{Context, Result};
+use napi::bindgen_prelude::BigInt;
+use ring::digest::{digest, SHA256};
+
+use crate::{
+    response::QueryResponseDataTyped,
+    util::{decode_hex, TxIndexes},
+};
+
+const INPUT_COIN: u8 = 0;
+const INPUT_MESSAGE: u8 = 2;
+const TX_STATUS_SUCCESS: u8 = 1;
+
+/// An input that was unlocked by a predicate.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PredicateSpend {
+    /// Root of the predicate, the owner of the coin or the recipient of the message
+    pub predicate_root: String,
+    /// sha256 of the predicate bytecode
+    pub bytecode_hash: String,
+    pub tx_id: String,
+    /// The status type of the transaction
+    pub tx_status: u8,
+    pub block_height: i64,
+    /// Index of the input in the transaction
+    pub input_index: i64,
+    /// The type of the input, 0 for coins and 2 for messages
+    pub input_type: u8,
+    /// Coin that was spent, not set for messages
+    pub utxo_id: Option<String>,
+    /// Nonce of the message that was spent, not set for coins
+    pub nonce: Option<String>,
+    pub asset_id: Option<String>,
+    pub amount: Option<BigInt>,
+    pub predicate_gas_used: Option<i64>,
+    /// Bytecode of the predicate
+    pub predicate: String,
+    /// Data that was passed to the predicate
+    pub predicate_data: Option<String>,
+}
+
+/// Amount of an asset.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct AssetAmount {
+    pub asset_id: String,
+    pub amount: BigInt,
+}
+
+/// Activity of the predicates that share the same bytecode.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PredicateStats {
+    /// sha256 of the predicate bytecode
+    pub bytecode_hash: String,
+    /// Roots of the predicates that were spent with this bytecode
+    pub predicate_roots: Vec<String>,
+    pub spend_count: i64,
+    /// Number of spends in transactions that failed
+    pub failed_spend_count: i64,
+    /// Gas used by the predicates to validate the spends
+    pub total_gas_used: i64,
+    /// Amount of each asset that was unlocked by the spends, ordered by asset id
+    pub assets_moved: Vec<AssetAmount>,
+    pub first_block_height: i64,
+    pub last_block_height: i64,
+}
+
+/// Stats of a bytecode along with the roots and asset amounts they are built from
+type Group<'a> = (PredicateStats, BTreeSet<&'a str>, BTreeMap<&'a str, u128>);
+
+/// Collects the spends of the given predicates across the pages of a query.
+pub struct PredicateTracker {
+    roots: HashSet<String>,
+    spends: Vec<PredicateSpend>,
+}
+
+impl PredicateTracker {
+    pub fn new(roots: HashSet<String>) -> Self {
+        Self {
+            roots,
+            spends: Vec::new(),
+        }
+    }
+
+    /// Add the predicate spends of a page, pages have to be added in block order.
+    pub fn add(&mut self, data: QueryResponseDataTyped) -> Result<()> {
+        let mut input_indexes = TxIndexes::default();
+        for input in data.inputs {
+            let input_index = input_indexes.next(&input.tx_id);
+
+            let root = match input.input_type {
+                INPUT_COIN => input.owner,
+                INPUT_MESSAGE => input.recipient,
+                _ => continue,
+            };
+            let Some(root) = root.filter(|root| self.roots.contains(root)) else {
+                continue;
+            };
+            // inputs of a predicate root that carry no bytecode were signed by a witness
+            let Some(predicate) = input.predicate.filter(|p| p.len() > 2) else {
+                continue;
+            };
+            let bytecode = decode_hex(&predicate).context("decode predicate bytecode")?;
+            let bytecode_hash = format!(
+                "0x{}",
+                faster_hex::hex_string(digest(&SHA256, &bytecode).as_ref())
+            );
+
+            self.spends.push(PredicateSpend {
+                predicate_root: root,
+                bytecode_hash,
+                tx_id: input.tx_id,
+                tx_status: input.tx_status,
+                block_height: input.block_height,
+                input_index,
+                input_type: input.input_type,
+                utxo_id: input.utxo_id,
+                nonce: input.nonce,
+                asset_id: input.asset_id,
+                amount: input.amount,
+                predicate_gas_used: input.predicate_gas_used,
+                predicate,
+                predicate_data: input.predicate_data,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Spends in block order along with the stats of each bytecode ordered by bytecode hash.
+    pub fn finish(self) -> (Vec<PredicateSpend>, Vec<PredicateStats>) {
+        let mut stats: BTreeMap<&str, Group> = BTreeMap::new();
+
+        for spend in self.spends.iter() {
+            let (stats, roots, assets) =
+                stats
+                    .entry(spend.bytecode_hash.as_str())
+                    .or_insert_with(|| {
+                        (
+                            PredicateStats {
+                                bytecode_hash: spend.bytecode_hash.clone(),
+                                predicate_roots: Vec::new(),
+                                spend_count: 0,
+                                failed_spend_count: 0,
+                                total_gas_used: 0,
+                                assets_moved: Vec::new(),
+                                first_block_height: spend.block_height,
+                                last_block_height: spend.block_height,
+                            },
+                            BTreeSet::new(),
+                            BTreeMap::new(),
+                        )
+                    });
+
+            stats.spend_count += 1;
+            if spend.tx_status != TX_STATUS_SUCCESS {
+                stats.failed_spend_count += 1;
+            }
+            stats.total_gas_used += spend.predicate_gas_used.unwrap_or_default();
+            stats.last_block_height = spend.block_height;
+            roots.insert(spend.predicate_root.as_str());
+            if let (Some(asset_id), Some(amount)) = (&spend.asset_id, &spend.amount) {
+                *assets.entry(asset_id.as_str()).or_default() += amount.get_u64().1 as u128;
+            }
+        }
+
+        let stats = stats
+            .into_values()
+            .map(|(mut stats, roots, assets)| {
+                stats.predicate_roots = roots.into_iter().map(|root| root.to_owned()).collect();
+                stats.assets_moved = assets
+                    .into_iter()
+                    .map(|(asset_id, amount)| AssetAmount {
+                        asset_id: asset_id.to_owned(),
+                        amount: BigInt::from(amount as i128),
+                    })
+                    .collect();
+                stats
+            })
+            .collect();
+
+        (self.spends, stats)
+    }
+}
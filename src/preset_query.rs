@@ -0,0 +1,379 @@
+use std::collections::BTreeSet;
+
+use hyperfuel_format::Hash;
+use hyperfuel_net_types::{
+    FieldSelection, InputSelection, OutputSelection, Query, ReceiptSelection,
+};
+
+/// Returns a query for all Log and LogData receipts emitted by any of the given contracts
+/// in successful transactions within the block range.
+pub fn logs(emitting_contracts: Vec<Hash>, from_block: u64, to_block: Option<u64>) -> Query {
+    let mut receipt_field_selection = BTreeSet::new();
+    receipt_field_selection.insert("block_height".to_owned());
+    receipt_field_selection.insert("tx_id".to_owned());
+    receipt_field_selection.insert("tx_status".to_owned());
+    receipt_field_selection.insert("receipt_index".to_owned());
+    receipt_field_selection.insert("receipt_type".to_owned());
+    receipt_field_selection.insert("contract_id".to_owned());
+    receipt_field_selection.insert("root_contract_id".to_owned());
+    receipt_field_selection.insert("ra".to_owned());
+    receipt_field_selection.insert("rb".to_owned());
+    receipt_field_selection.insert("rc".to_owned());
+    receipt_field_selection.insert("rd".to_owned());
+    receipt_field_selection.insert("pc".to_owned());
+    receipt_field_selection.insert("is".to_owned());
+    receipt_field_selection.insert("ptr".to_owned());
+    receipt_field_selection.insert("len".to_owned());
+    receipt_field_selection.insert("digest".to_owned());
+    receipt_field_selection.insert("data".to_owned());
+
+    Query {
+        from_block,
+        to_block,
+        receipts: vec![ReceiptSelection {
+            root_contract_id: emitting_contracts,
+            receipt_type: vec![5, 6],
+            tx_status: vec![1],
+            ..Default::default()
+        }],
+        field_selection: FieldSelection {
+            receipt: receipt_field_selection,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for all Mint and Burn receipts of the given contracts
+/// in successful transactions within the block range.
+pub fn mints_burns(contracts: Vec<Hash>, from_block: u64, to_block: Option<u64>) -> Query {
+    let mut receipt_field_selection = BTreeSet::new();
+    receipt_field_selection.insert("block_height".to_owned());
+    receipt_field_selection.insert("tx_id".to_owned());
+    receipt_field_selection.insert("tx_status".to_owned());
+    receipt_field_selection.insert("receipt_index".to_owned());
+    receipt_field_selection.insert("receipt_type".to_owned());
+    receipt_field_selection.insert("contract_id".to_owned());
+    receipt_field_selection.insert("sub_id".to_owned());
+    receipt_field_selection.insert("val".to_owned());
+
+    Query {
+        from_block,
+        to_block,
+        receipts: vec![ReceiptSelection {
+            contract_id: contracts,
+            receipt_type: vec![11, 12],
+            tx_status: vec![1],
+            ..Default::default()
+        }],
+        field_selection: FieldSelection {
+            receipt: receipt_field_selection,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for the transactions that moved any of the given assets from or to any of
+/// the given addresses or contracts within the block range.
+///
+/// Matches Transfer and TransferOut receipts, coin inputs and coin, change and variable
+/// outputs. Empty lists match everything. Every input, output and receipt of the matched
+/// transactions is returned, so the response has to be filtered again on the client.
+///
+/// Transfer receipts can't be selected by their recipient contract, so the receipts of
+/// contracts are selected by the contract that sent them.
+pub fn transfers(
+    asset_ids: Vec<Hash>,
+    addresses: Vec<Hash>,
+    contracts: Vec<Hash>,
+    from_block: u64,
+    to_block: Option<u64>,
+    include_failed: bool,
+) -> Query {
+    let tx_status = if include_failed { vec![] } else { vec![1] };
+    let any_party = addresses.is_empty() && contracts.is_empty();
+
+    let mut receipts = Vec::new();
+    if any_party {
+        receipts.push(ReceiptSelection {
+            receipt_type: vec![7, 8],
+            asset_id: asset_ids.clone(),
+            tx_status: tx_status.clone(),
+            ..Default::default()
+        });
+    }
+    if !addresses.is_empty() {
+        receipts.push(ReceiptSelection {
+            receipt_type: vec![8],
+            to_address: addresses.clone(),
+            asset_id: asset_ids.clone(),
+            tx_status: tx_status.clone(),
+            ..Default::default()
+        });
+    }
+    if !contracts.is_empty() {
+        receipts.push(ReceiptSelection {
+            receipt_type: vec![7, 8],
+            contract_id: contracts,
+            asset_id: asset_ids.clone(),
+            tx_status: tx_status.clone(),
+            ..Default::default()
+        });
+    }
+
+    // coins are only owned by addresses
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    if any_party || !addresses.is_empty() {
+        inputs.push(InputSelection {
+            input_type: vec![0],
+            owner: addresses.clone(),
+            asset_id: asset_ids.clone(),
+            tx_status: tx_status.clone(),
+            ..Default::default()
+        });
+        outputs.push(OutputSelection {
+            output_type: vec![0, 2, 3],
+            to: addresses,
+            asset_id: asset_ids,
+            tx_status,
+            ..Default::default()
+        });
+    }
+
+    let fields = |names: &[&str]| names.iter().map(|&name| name.to_owned()).collect();
+
+    Query {
+        from_block,
+        to_block,
+        receipts,
+        inputs,
+        outputs,
+        field_selection: FieldSelection {
+            receipt: fields(&[
+                "block_height",
+                "tx_id",
+                "tx_status",
+                "receipt_index",
+                "receipt_type",
+                "contract_id",
+                "to",
+                "to_address",
+                "asset_id",
+                "amount",
+            ]),
+            input: fields(&[
+                "block_height",
+                "tx_id",
+                "tx_status",
+                "input_type",
+                "owner",
+                "asset_id",
+                "amount",
+            ]),
+            output: fields(&[
+                "block_height",
+                "tx_id",
+                "tx_status",
+                "output_type",
+                "to",
+                "asset_id",
+                "amount",
+            ]),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for the ContractCreated outputs of successful transactions within the
+/// block range, along with their deploying transactions.
+///
+/// Only the given contracts are selected if the list isn't empty. The witnesses of the
+/// transactions are selected if `include_bytecode` is set.
+pub fn contract_deployments(
+    contract_ids: Vec<Hash>,
+    from_block: u64,
+    to_block: Option<u64>,
+    include_bytecode: bool,
+) -> Query {
+    let mut output_field_selection = BTreeSet::new();
+    output_field_selection.insert("block_height".to_owned());
+    output_field_selection.insert("tx_id".to_owned());
+    output_field_selection.insert("output_type".to_owned());
+    output_field_selection.insert("contract".to_owned());
+    output_field_selection.insert("state_root".to_owned());
+
+    let mut tx_field_selection = BTreeSet::new();
+    tx_field_selection.insert("block_height".to_owned());
+    tx_field_selection.insert("id".to_owned());
+    tx_field_selection.insert("time".to_owned());
+    tx_field_selection.insert("salt".to_owned());
+    tx_field_selection.insert("bytecode_root".to_owned());
+    tx_field_selection.insert("bytecode_witness_index".to_owned());
+    if include_bytecode {
+        tx_field_selection.insert("witnesses".to_owned());
+    }
+
+    Query {
+        from_block,
+        to_block,
+        outputs: vec![OutputSelection {
+            contract: contract_ids,
+            output_type: vec![4],
+            tx_status: vec![1],
+            ..Default::default()
+        }],
+        field_selection: FieldSelection {
+            transaction: tx_field_selection,
+            output: output_field_selection,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for the messages sent between the base layer and Fuel in successful
+/// transactions within the block range, MessageOut receipts and InputMessage inputs.
+///
+/// Only messages from the given senders and to the given recipients are selected if the
+/// lists aren't empty.
+pub fn bridge_messages(
+    senders: Vec<Hash>,
+    recipients: Vec<Hash>,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Query {
+    let mut receipt_field_selection = BTreeSet::new();
+    receipt_field_selection.insert("block_height".to_owned());
+    receipt_field_selection.insert("tx_id".to_owned());
+    receipt_field_selection.insert("tx_status".to_owned());
+    receipt_field_selection.insert("receipt_index".to_owned());
+    receipt_field_selection.insert("receipt_type".to_owned());
+    receipt_field_selection.insert("sender".to_owned());
+    receipt_field_selection.insert("recipient".to_owned());
+    receipt_field_selection.insert("nonce".to_owned());
+    receipt_field_selection.insert("amount".to_owned());
+    receipt_field_selection.insert("digest".to_owned());
+    receipt_field_selection.insert("data".to_owned());
+
+    let mut input_field_selection = BTreeSet::new();
+    input_field_selection.insert("block_height".to_owned());
+    input_field_selection.insert("tx_id".to_owned());
+    input_field_selection.insert("tx_status".to_owned());
+    input_field_selection.insert("input_type".to_owned());
+    input_field_selection.insert("sender".to_owned());
+    input_field_selection.insert("recipient".to_owned());
+    input_field_selection.insert("nonce".to_owned());
+    input_field_selection.insert("amount".to_owned());
+    input_field_selection.insert("data".to_owned());
+
+    Query {
+        from_block,
+        to_block,
+        receipts: vec![ReceiptSelection {
+            sender: senders.clone(),
+            recipient: recipients.clone(),
+            receipt_type: vec![10],
+            tx_status: vec![1],
+            ..Default::default()
+        }],
+        inputs: vec![InputSelection {
+            sender: senders,
+            recipient: recipients,
+            input_type: vec![2],
+            tx_status: vec![1],
+            ..Default::default()
+        }],
+        field_selection: FieldSelection {
+            receipt: receipt_field_selection,
+            input: input_field_selection,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for the coins created for and spent by the given owners, the coins of
+/// every owner are selected if no owners are given.
+pub fn utxos(owners: Vec<Hash>, from_block: u64, to_block: Option<u64>) -> Query {
+    let fields = |names: &[&str]| names.iter().map(|&name| name.to_owned()).collect();
+
+    Query {
+        from_block,
+        to_block,
+        inputs: vec![InputSelection {
+            input_type: vec![0],
+            owner: owners.clone(),
+            ..Default::default()
+        }],
+        outputs: vec![OutputSelection {
+            output_type: vec![0, 2, 3],
+            to: owners,
+            ..Default::default()
+        }],
+        field_selection: FieldSelection {
+            input: fields(&[
+                "block_height",
+                "tx_id",
+                "input_type",
+                "utxo_id",
+                "owner",
+                "asset_id",
+                "amount",
+            ]),
+            output: fields(&[
+                "block_height",
+                "tx_id",
+                "output_type",
+                "to",
+                "asset_id",
+                "amount",
+            ]),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns a query for the coin and message inputs owned by the given predicate roots.
+pub fn predicate_spends(roots: Vec<Hash>, from_block: u64, to_block: Option<u64>) -> Query {
+    let fields = |names: &[&str]| names.iter().map(|&name| name.to_owned()).collect();
+
+    Query {
+        from_block,
+        to_block,
+        inputs: vec![
+            InputSelection {
+                input_type: vec![0],
+                owner: roots.clone(),
+                ..Default::default()
+            },
+            InputSelection {
+                input_type: vec![2],
+                recipient: roots,
+                ..Default::default()
+            },
+        ],
+        field_selection: FieldSelection {
+            input: fields(&[
+                "block_height",
+                "tx_id",
+                "tx_status",
+                "input_type",
+                "utxo_id",
+                "owner",
+                "recipient",
+                "nonce",
+                "asset_id",
+                "amount",
+                "predicate_gas_used",
+                "predicate",
+                "predicate_data",
+            ]),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
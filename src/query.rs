@@ -176,6 +176,24 @@ pub struct Query {
     pub max_num_transactions: Option<i64>,
 }
 
+#[napi(object)]
+#[derive(Default, Clone, Debug)]
+pub struct BatchOptions {
+    /// Maximum number of queries to run at the same time, default is 4
+    pub concurrency: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default, Clone, Debug)]
+pub struct ParallelOptions {
+    /// Maximum number of sub-ranges to fetch at the same time, default is 4
+    pub concurrency: Option<i64>,
+    /// Number of blocks in the first sub-ranges. Later sub-ranges are sized by how fast the
+    /// server progressed through the earlier ones. By default the range is split into
+    /// 4 sub-ranges per concurrent request.
+    pub initial_chunk_blocks: Option<i64>,
+}
+
 impl Query {
     pub fn try_convert(&self) -> Result<hyperfuel_net_types::Query> {
         let json = serde_json::to_vec(self).context("serialize to json")?;
@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::Config;
+
+static GROUPS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+
+/// Limits the rate and the number of concurrent requests of one or more clients.
+pub struct RateLimiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+struct TokenBucket {
+    per_second: f64,
+    burst: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Slot of a request, the request counts as in flight until this is dropped.
+pub struct Permit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    /// Build the rate limiter configured in `cfg`.
+    ///
+    /// Returns the limiter of the rate limit group if the config has one, so clients in
+    /// the same group share it. Returns `None` if no limits are configured.
+    pub fn from_config(cfg: &Config) -> Result<Option<Arc<Self>>> {
+        let Some(group) = &cfg.rate_limit_group else {
+            return Ok(Self::new(cfg)?.map(Arc::new));
+        };
+
+        let mut groups = GROUPS.get_or_init(Default::default).lock().unwrap();
+        if let Some(limiter) = groups.get(group) {
+            return Ok(Some(limiter.clone()));
+        }
+        let limiter = match Self::new(cfg)? {
+            Some(limiter) => Arc::new(limiter),
+            None => return Ok(None),
+        };
+        groups.insert(group.clone(), limiter.clone());
+
+        Ok(Some(limiter))
+    }
+
+    fn new(cfg: &Config) -> Result<Option<Self>> {
+        let bucket = match cfg.max_requests_per_second {
+            Some(per_second) => {
+                if per_second.is_nan() || per_second <= 0.0 {
+                    return Err(anyhow!("max_requests_per_second has to be positive"));
+                }
+                let burst = cfg.request_burst.unwrap_or(1);
+                if burst < 1 {
+                    return Err(anyhow!("request_burst has to be at least 1"));
+                }
+                Some(Mutex::new(TokenBucket {
+                    per_second,
+                    burst: burst as f64,
+                    tokens: burst as f64,
+                    last_refill: Instant::now(),
+                }))
+            }
+            None => None,
+        };
+
+        let in_flight = match cfg.max_concurrent_requests {
+            Some(max) => {
+                let max: usize = max
+                    .try_into()
+                    .ok()
+                    .filter(|&max| max > 0)
+                    .ok_or_else(|| anyhow!("max_concurrent_requests has to be at least 1"))?;
+                Some(Arc::new(Semaphore::new(max)))
+            }
+            None => None,
+        };
+
+        if bucket.is_none() && in_flight.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { bucket, in_flight }))
+    }
+
+    /// Wait until a request can be sent.
+    pub async fn acquire(&self) -> Permit {
+        let in_flight = match &self.in_flight {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        if let Some(bucket) = &self.bucket {
+            let wait = bucket.lock().unwrap().reserve();
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        Permit {
+            _in_flight: in_flight,
+        }
+    }
+}
+
+impl TokenBucket {
+    /// Take a token and return how long to wait until it is available.
+    ///
+    /// Tokens can go negative, so concurrent callers queue up behind each other instead of
+    /// all waking up at the same time.
+    fn reserve(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst);
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
@@ -1,6 +1,18 @@
 use hyperfuel_format::Hex;
 
-use crate::types::{as_i64, Block, Input, Output, Receipt, Transaction};
+use crate::{
+    asset::MintBurnEvent,
+    balances::BalanceHistory,
+    bridge::BridgeMessage,
+    deployments::ContractDeployment,
+    error::RateLimited,
+    join::TransactionContext,
+    metrics::RequestMetrics,
+    predicates::{PredicateSpend, PredicateStats},
+    transfers::Transfer,
+    types::{as_i64, Block, Input, Output, Receipt, Transaction},
+    utxo::UtxoLifecycle,
+};
 
 #[napi(object)]
 #[derive(Debug, Clone)]
@@ -15,6 +27,20 @@ pub struct QueryResponseTyped {
     pub total_execution_time: i64,
     /// Response data
     pub data: QueryResponseDataTyped,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+/// Outcome of a single query of `getDataBatch`, exactly one of `response` and `error` is set.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// Response of the query if it succeeded
+    pub response: Option<QueryResponseTyped>,
+    /// Error message if the query failed, same as the message `getData` would throw
+    pub error: Option<String>,
+    /// Rate limit details if the query failed because the server rate limited the client
+    pub rate_limited: Option<RateLimited>,
 }
 
 #[napi(object)]
@@ -45,6 +71,7 @@ impl From<hyperfuel_client::QueryResponseTyped> for QueryResponseTyped {
             next_block,
             total_execution_time,
             data,
+            metrics: RequestMetrics::default(),
         }
     }
 }
@@ -62,6 +89,134 @@ pub struct LogResponse {
     pub total_execution_time: i64,
     /// Response data
     pub data: Vec<LogContext>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransactionsResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so
+    /// the caller should continue the query from this block if they
+    /// didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Transactions with their block, receipts, inputs and outputs
+    pub data: Vec<TransactionContext>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct TransferResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so
+    /// the caller should continue the query from this block if they
+    /// didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Transfers ordered by block and transaction
+    pub data: Vec<Transfer>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct MintBurnResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so
+    /// the caller should continue the query from this block if they
+    /// didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Mint and burn events ordered by block and receipt index
+    pub data: Vec<MintBurnEvent>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct ContractDeploymentResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so
+    /// the caller should continue the query from this block if they
+    /// didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Deployed contracts ordered by block
+    pub data: Vec<ContractDeployment>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BridgeMessageResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Next block to query for, the responses are paginated so
+    /// the caller should continue the query from this block if they
+    /// didn't get responses up to the to_block they specified in the Query.
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the query.
+    pub total_execution_time: i64,
+    /// Deposits and withdrawals ordered by block
+    pub data: Vec<BridgeMessage>,
+    /// Client side metrics of the request
+    pub metrics: RequestMetrics,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct BalanceResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Block the balances were computed up to, exclusive
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the queries.
+    pub total_execution_time: i64,
+    /// Balance histories ordered by address and asset id
+    pub data: Vec<BalanceHistory>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct PredicateActivityResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Block the activity was collected up to, exclusive
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the queries.
+    pub total_execution_time: i64,
+    /// Predicate spends ordered by block
+    pub spends: Vec<PredicateSpend>,
+    /// Stats of each predicate bytecode ordered by bytecode hash
+    pub stats: Vec<PredicateStats>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct UtxoLifecycleResponse {
+    /// Current height of the source hypersync instance
+    pub archive_height: Option<i64>,
+    /// Block the lifecycles were tracked up to, exclusive
+    pub next_block: i64,
+    /// Total time it took the hypersync instance to execute the queries.
+    pub total_execution_time: i64,
+    /// Coins ordered by the block they were created in
+    pub data: Vec<UtxoLifecycle>,
 }
 
 /// Contains all the fields needed for decoding plus some additional fields
@@ -120,6 +275,7 @@ impl From<hyperfuel_client::LogResponse> for LogResponse {
             next_block,
             total_execution_time,
             data,
+            metrics: RequestMetrics::default(),
         }
     }
 }
@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Context, Result};
+use arrow2::{
+    array::{Array, BinaryArray, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Schema},
+    io::ipc::write::{FileWriter, WriteOptions},
+};
+use napi::bindgen_prelude::BigInt;
+
+use crate::{
+    types::{Block, Input, Output, Receipt, Transaction},
+    util::decode_hex,
+};
+
+/// Value of a single column of a row.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cell {
+    UInt(u64),
+    Int(i64),
+    Binary(Vec<u8>),
+    Utf8(String),
+}
+
+impl Cell {
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Cell::UInt(v) => Some(*v),
+            Cell::Int(v) => Some(*v as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Cell::Binary(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// A row of a table keyed by the column names of the hyperfuel schema.
+/// Missing columns are null.
+#[derive(Debug, Clone, Default)]
+pub struct Row(BTreeMap<&'static str, Cell>);
+
+impl Row {
+    pub fn get(&self, column: &str) -> Option<&Cell> {
+        self.0.get(column)
+    }
+
+    fn uint(&mut self, column: &'static str, v: impl Into<Option<i64>>) {
+        if let Some(v) = v.into() {
+            self.0.insert(column, Cell::UInt(v as u64));
+        }
+    }
+
+    fn int(&mut self, column: &'static str, v: i64) {
+        self.0.insert(column, Cell::Int(v));
+    }
+
+    fn big(&mut self, column: &'static str, v: &Option<BigInt>) {
+        if let Some(v) = v {
+            self.0.insert(column, Cell::UInt(v.get_u64().1));
+        }
+    }
+
+    fn decimal(&mut self, column: &'static str, v: &Option<String>) -> Result<()> {
+        if let Some(v) = v {
+            let v = v
+                .parse()
+                .with_context(|| format!("parse {} as decimal integer", column))?;
+            self.0.insert(column, Cell::UInt(v));
+        }
+        Ok(())
+    }
+
+    fn quantity(&mut self, column: &'static str, v: &str) -> Result<()> {
+        let bytes = decode_hex(v).with_context(|| format!("decode {}", column))?;
+        if bytes.len() > 8 {
+            return Err(anyhow!("{} doesn't fit into 64 bits", column));
+        }
+        let v = bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+        self.0.insert(column, Cell::UInt(v));
+        Ok(())
+    }
+
+    fn hex<'a>(&mut self, column: &'static str, v: impl Into<Option<&'a String>>) -> Result<()> {
+        if let Some(v) = v.into() {
+            let bytes = decode_hex(v).with_context(|| format!("decode {}", column))?;
+            self.0.insert(column, Cell::Binary(bytes));
+        }
+        Ok(())
+    }
+
+    /// Binary column of a big quantity, stored without leading zero bytes like the server does.
+    fn hex_quantity<'a>(
+        &mut self,
+        column: &'static str,
+        v: impl Into<Option<&'a String>>,
+    ) -> Result<()> {
+        if let Some(v) = v.into() {
+            let bytes = decode_hex(v).with_context(|| format!("decode {}", column))?;
+            let start = bytes
+                .iter()
+                .position(|&b| b != 0)
+                .unwrap_or(bytes.len().saturating_sub(1));
+            self.0.insert(column, Cell::Binary(bytes[start..].to_vec()));
+        }
+        Ok(())
+    }
+
+    fn hex_list(&mut self, column: &'static str, v: &Option<Vec<String>>) -> Result<()> {
+        if let Some(v) = v {
+            let mut bytes = Vec::new();
+            for item in v {
+                bytes.extend(decode_hex(item).with_context(|| format!("decode {}", column))?);
+            }
+            self.0.insert(column, Cell::Binary(bytes));
+        }
+        Ok(())
+    }
+
+    fn utf8(&mut self, column: &'static str, v: &Option<String>) {
+        if let Some(v) = v {
+            self.0.insert(column, Cell::Utf8(v.clone()));
+        }
+    }
+}
+
+pub fn block_row(b: &Block) -> Result<Row> {
+    let mut row = Row::default();
+    row.hex("id", &b.id)?;
+    row.uint("da_height", b.da_height);
+    row.uint(
+        "consensus_parameters_version",
+        b.consensus_parameters_version,
+    );
+    row.uint(
+        "state_transition_bytecode_version",
+        b.state_transition_bytecode_version,
+    );
+    row.quantity("transactions_count", &b.transactions_count)?;
+    row.quantity("message_receipt_count", &b.message_receipt_count)?;
+    row.hex("transactions_root", &b.transactions_root)?;
+    row.hex("message_outbox_root", &b.message_outbox_root)?;
+    row.hex("event_inbox_root", &b.event_inbox_root)?;
+    row.uint("height", b.height);
+    row.hex("prev_root", &b.prev_root)?;
+    row.int("time", b.time);
+    row.hex("application_hash", &b.application_hash)?;
+    Ok(row)
+}
+
+pub fn transaction_row(t: &Transaction) -> Result<Row> {
+    let mut row = Row::default();
+    row.uint("block_height", t.block_height);
+    row.hex("id", &t.id)?;
+    row.hex_list("input_asset_ids", &t.input_asset_ids)?;
+    row.hex_list("input_contracts", &t.input_contracts)?;
+    row.hex("input_contract_utxo_id", &t.input_contract_utxo_id)?;
+    row.hex(
+        "input_contract_balance_root",
+        &t.input_contract_balance_root,
+    )?;
+    row.hex("input_contract_state_root", &t.input_contract_state_root)?;
+    row.uint(
+        "input_contract_tx_pointer_block_height",
+        t.input_contract_tx_pointer_block_height,
+    );
+    row.uint(
+        "input_contract_tx_pointer_tx_index",
+        t.input_contract_tx_pointer_tx_index,
+    );
+    row.hex("input_contract", &t.input_contract)?;
+    row.uint("policies_tip", t.policies_tip);
+    row.uint("policies_witness_limit", t.policies_witness_limit);
+    row.uint("policies_maturity", t.policies_maturity);
+    row.uint("policies_max_fee", t.policies_max_fee);
+    row.uint("script_gas_limit", t.script_gas_limit);
+    row.uint("maturity", t.maturity);
+    row.uint("mint_amount", t.mint_amount);
+    row.hex("mint_asset_id", &t.mint_asset_id)?;
+    row.uint("mint_gas_price", t.mint_gas_price);
+    row.uint("tx_pointer_block_height", t.tx_pointer_block_height);
+    row.uint("tx_pointer_tx_index", t.tx_pointer_tx_index);
+    row.uint("tx_type", i64::from(t.tx_type));
+    row.uint("output_contract_input_index", t.output_contract_input_index);
+    row.hex(
+        "output_contract_balance_root",
+        &t.output_contract_balance_root,
+    )?;
+    row.hex("output_contract_state_root", &t.output_contract_state_root)?;
+    row.hex("witnesses", &t.witnesses)?;
+    row.hex("receipts_root", &t.receipts_root)?;
+    row.uint("status", i64::from(t.status));
+    row.int("time", t.time);
+    row.utf8("reason", &t.reason);
+    row.hex("script", &t.script)?;
+    row.hex("script_data", &t.script_data)?;
+    row.uint("bytecode_witness_index", t.bytecode_witness_index);
+    row.hex("bytecode_root", &t.bytecode_root)?;
+    row.uint("subsection_index", t.subsection_index);
+    row.uint("subsections_number", t.subsections_number);
+    row.hex("proof_set", &t.proof_set)?;
+    row.uint(
+        "consensus_parameters_upgrade_purpose_witness_index",
+        t.consensus_parameters_upgrade_purpose_witness_index,
+    );
+    row.hex(
+        "consensus_parameters_upgrade_purpose_checksum",
+        &t.consensus_parameters_upgrade_purpose_checksum,
+    )?;
+    row.hex(
+        "state_transition_upgrade_purpose_root",
+        &t.state_transition_upgrade_purpose_root,
+    )?;
+    row.hex("salt", &t.salt)?;
+    Ok(row)
+}
+
+pub fn receipt_row(r: &Receipt) -> Result<Row> {
+    let mut row = Row::default();
+    row.uint("receipt_index", r.receipt_index);
+    row.hex("root_contract_id", &r.root_contract_id)?;
+    row.hex("tx_id", &r.tx_id)?;
+    row.uint("tx_status", i64::from(r.tx_status));
+    row.uint("tx_type", i64::from(r.tx_type));
+    row.uint("block_height", r.block_height);
+    row.decimal("pc", &r.pc)?;
+    row.decimal("is", &r.is)?;
+    row.hex("to", &r.to)?;
+    row.hex("to_address", &r.to_address)?;
+    row.big("amount", &r.amount);
+    row.hex("asset_id", &r.asset_id)?;
+    row.uint("gas", r.gas);
+    row.big("param1", &r.param1);
+    row.big("param2", &r.param2);
+    row.big("val", &r.val);
+    row.big("ptr", &r.ptr);
+    row.hex("digest", &r.digest)?;
+    row.uint("reason", r.reason);
+    row.big("ra", &r.ra);
+    row.big("rb", &r.rb);
+    row.big("rc", &r.rc);
+    row.big("rd", &r.rd);
+    row.big("len", &r.len);
+    row.uint("receipt_type", i64::from(r.receipt_type));
+    row.uint("result", r.result);
+    row.uint("gas_used", r.gas_used);
+    row.hex("data", &r.data)?;
+    row.hex("sender", &r.sender)?;
+    row.hex("recipient", &r.recipient)?;
+    row.hex_quantity("nonce", &r.nonce)?;
+    row.hex("contract_id", &r.contract_id)?;
+    row.hex("sub_id", &r.sub_id)?;
+    Ok(row)
+}
+
+pub fn input_row(i: &Input) -> Result<Row> {
+    let mut row = Row::default();
+    row.hex("tx_id", &i.tx_id)?;
+    row.uint("tx_status", i64::from(i.tx_status));
+    row.uint("tx_type", i64::from(i.tx_type));
+    row.uint("block_height", i.block_height);
+    row.uint("input_type", i64::from(i.input_type));
+    row.hex("utxo_id", &i.utxo_id)?;
+    row.hex("owner", &i.owner)?;
+    row.big("amount", &i.amount);
+    row.hex("asset_id", &i.asset_id)?;
+    row.uint("tx_pointer_block_height", i.tx_pointer_block_height);
+    row.uint("tx_pointer_tx_index", i.tx_pointer_tx_index);
+    row.uint("witness_index", i.witness_index);
+    row.uint("predicate_gas_used", i.predicate_gas_used);
+    row.hex("predicate", &i.predicate)?;
+    row.hex("predicate_data", &i.predicate_data)?;
+    row.hex("balance_root", &i.balance_root)?;
+    row.hex("state_root", &i.state_root)?;
+    row.hex("contract", &i.contract)?;
+    row.hex("sender", &i.sender)?;
+    row.hex("recipient", &i.recipient)?;
+    row.hex("nonce", &i.nonce)?;
+    row.hex("data", &i.data)?;
+    Ok(row)
+}
+
+pub fn output_row(o: &Output) -> Result<Row> {
+    let mut row = Row::default();
+    row.hex("tx_id", &o.tx_id)?;
+    row.uint("tx_status", i64::from(o.tx_status));
+    row.uint("tx_type", i64::from(o.tx_type));
+    row.uint("block_height", o.block_height);
+    row.uint("output_type", i64::from(o.output_type));
+    row.hex("to", &o.to)?;
+    row.big("amount", &o.amount);
+    row.hex("asset_id", &o.asset_id)?;
+    row.uint("input_index", o.input_index);
+    row.hex("balance_root", &o.balance_root)?;
+    row.hex("state_root", &o.state_root)?;
+    row.hex("contract", &o.contract)?;
+    Ok(row)
+}
+
+/// Encode the rows into an arrow ipc file with the selected columns of the schema.
+pub fn rows_to_ipc(
+    schema: &Schema,
+    field_selection: &BTreeSet<String>,
+    rows: &[&Row],
+) -> Result<Vec<u8>> {
+    let schema =
+        hyperfuel_schema::project_schema(schema, field_selection).context("project schema")?;
+
+    let columns = schema
+        .fields
+        .iter()
+        .map(|field| column(field.name.as_str(), field.data_type(), rows))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut out = Vec::new();
+    let mut writer =
+        FileWriter::try_new(&mut out, schema, None, WriteOptions { compression: None })
+            .context("start ipc file")?;
+    if !rows.is_empty() && !columns.is_empty() {
+        writer
+            .write(&Chunk::new(columns), None)
+            .context("write ipc chunk")?;
+    }
+    writer.finish().context("finish ipc file")?;
+
+    Ok(out)
+}
+
+fn column(name: &str, data_type: &DataType, rows: &[&Row]) -> Result<Box<dyn Array>> {
+    let cells = rows.iter().map(|row| row.get(name));
+
+    let array: Box<dyn Array> = match data_type {
+        DataType::UInt64 => Box::new(PrimitiveArray::<u64>::from(
+            cells.map(|c| c.and_then(Cell::as_u64)).collect::<Vec<_>>(),
+        )),
+        DataType::UInt8 => Box::new(PrimitiveArray::<u8>::from(
+            cells
+                .map(|c| c.and_then(Cell::as_u64).map(|v| v as u8))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Int64 => Box::new(PrimitiveArray::<i64>::from(
+            cells
+                .map(|c| c.and_then(Cell::as_u64).map(|v| v as i64))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Binary => Box::new(
+            cells
+                .map(|c| c.and_then(Cell::as_bytes))
+                .collect::<BinaryArray<i32>>(),
+        ),
+        DataType::Utf8 => Box::new(
+            cells
+                .map(|c| match c {
+                    Some(Cell::Utf8(v)) => Some(v.as_str()),
+                    _ => None,
+                })
+                .collect::<Utf8Array<i32>>(),
+        ),
+        dt => return Err(anyhow!("unsupported data type {:?} of column {}", dt, name)),
+    };
+
+    Ok(array)
+}
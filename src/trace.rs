@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use serde_json::Value;
+
+use crate::auth;
+
+/// A finished span of a client operation.
+///
+/// Ids follow the W3C trace context format so spans can be handed to an OpenTelemetry exporter.
+#[napi(object)]
+#[derive(Debug, Default, Clone)]
+pub struct SpanData {
+    /// Name of the operation
+    pub name: String,
+    /// Hex encoded 16 byte trace id
+    pub trace_id: String,
+    /// Hex encoded 8 byte span id
+    pub span_id: String,
+    /// Hex encoded 8 byte id of the parent span, not set for a root span without a traceparent
+    pub parent_span_id: Option<String>,
+    /// Start time in milliseconds since unix epoch
+    pub start_time_ms: f64,
+    /// End time in milliseconds since unix epoch
+    pub end_time_ms: f64,
+    pub attributes: HashMap<String, Value>,
+    /// Error message if the operation failed
+    pub error: Option<String>,
+}
+
+pub type SpanHandler = ThreadsafeFunction<SpanData, ErrorStrategy::Fatal>;
+
+/// Hands finished spans over to the javascript callback if one is registered.
+#[derive(Default)]
+pub struct SpanExporter {
+    handler: Mutex<Option<SpanHandler>>,
+}
+
+impl SpanExporter {
+    pub fn set_handler(&self, handler: SpanHandler) {
+        *self.handler.lock().unwrap() = Some(handler);
+    }
+
+    fn export(&self, span: SpanData) {
+        if let Some(handler) = self.handler.lock().unwrap().as_ref() {
+            handler.call(span, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+}
+
+/// A span that is exported when it is dropped.
+pub struct Span {
+    data: SpanData,
+    flags: u8,
+    exporter: Arc<SpanExporter>,
+}
+
+impl Span {
+    /// Start a root span.
+    ///
+    /// Continues the trace of the given W3C traceparent, starts a new trace if there is none
+    /// or if it is malformed.
+    pub fn root(name: &str, traceparent: Option<&str>, exporter: Arc<SpanExporter>) -> Self {
+        let (trace_id, parent_span_id, flags) = match traceparent.and_then(parse_traceparent) {
+            Some((trace_id, parent_span_id, flags)) => (trace_id, Some(parent_span_id), flags),
+            None => (random_hex(16), None, 1),
+        };
+
+        Self::new(name, trace_id, parent_span_id, flags, exporter)
+    }
+
+    /// Start a span that is a child of this span.
+    pub fn child(&self, name: &str) -> Self {
+        Self::new(
+            name,
+            self.data.trace_id.clone(),
+            Some(self.data.span_id.clone()),
+            self.flags,
+            self.exporter.clone(),
+        )
+    }
+
+    fn new(
+        name: &str,
+        trace_id: String,
+        parent_span_id: Option<String>,
+        flags: u8,
+        exporter: Arc<SpanExporter>,
+    ) -> Self {
+        Self {
+            data: SpanData {
+                name: name.to_owned(),
+                trace_id,
+                span_id: random_hex(8),
+                parent_span_id,
+                start_time_ms: now_ms(),
+                ..Default::default()
+            },
+            flags,
+            exporter,
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<Value>) {
+        self.data.attributes.insert(key.to_owned(), value.into());
+    }
+
+    /// Record the error of the operation, bearer tokens are redacted from it.
+    pub fn set_error(&mut self, err: &anyhow::Error) {
+        self.data.error = Some(auth::redact_error(err));
+    }
+
+    /// W3C traceparent header value that makes this span the parent of the receiver.
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.data.trace_id, self.data.span_id, self.flags
+        )
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let mut data = std::mem::take(&mut self.data);
+        data.end_time_ms = now_ms();
+        self.exporter.export(data);
+    }
+}
+
+// parses "{version}-{trace_id}-{parent_id}-{flags}" as defined in https://www.w3.org/TR/trace-context/
+fn parse_traceparent(traceparent: &str) -> Option<(String, String, u8)> {
+    let parts: Vec<&str> = traceparent.trim().split('-').collect();
+    if parts.len() < 4 {
+        return None;
+    }
+
+    let is_hex = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit());
+    let is_zero = |s: &str| s.bytes().all(|b| b == b'0');
+
+    let (version, trace_id, parent_id, flags) = (parts[0], parts[1], parts[2], parts[3]);
+    if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.len() != 4) {
+        return None;
+    }
+    if !is_hex(trace_id, 32) || is_zero(trace_id) || !is_hex(parent_id, 16) || is_zero(parent_id) {
+        return None;
+    }
+    if !is_hex(flags, 2) {
+        return None;
+    }
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+
+    Some((
+        trace_id.to_ascii_lowercase(),
+        parent_id.to_ascii_lowercase(),
+        flags,
+    ))
+}
+
+fn random_hex(num_bytes: usize) -> String {
+    let bytes: Vec<u8> = (0..num_bytes).map(|_| rand::random()).collect();
+    faster_hex::hex_string(&bytes)
+}
+
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use napi::bindgen_prelude::BigInt;
+
+use crate::{response::QueryResponseDataTyped, util::TxIndexes};
+
+const TRANSFER: u8 = 7;
+const TRANSFER_OUT: u8 = 8;
+const INPUT_COIN: u8 = 0;
+const OUTPUT_COIN: u8 = 0;
+const OUTPUT_CHANGE: u8 = 2;
+const OUTPUT_VARIABLE: u8 = 3;
+const TX_STATUS_SUCCESS: u8 = 1;
+
+/// A movement of an asset, normalized from a receipt, coin input or coin output.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    /// "transfer" for Transfer receipts (contract to contract), "transfer_out" for
+    /// TransferOut receipts (contract to address), "coin_input" for spent coins,
+    /// "coin_output", "change_output" or "variable_output" for created coins
+    pub kind: String,
+    /// Contract or address the asset moved from, not set for outputs
+    pub from: Option<String>,
+    /// Contract or address the asset moved to, not set for inputs
+    pub to: Option<String>,
+    pub asset_id: Option<String>,
+    pub amount: Option<BigInt>,
+    pub tx_id: String,
+    /// The status type of the transaction
+    pub tx_status: u8,
+    pub block_height: i64,
+    /// Index of the receipt for transfer and transfer_out records
+    pub receipt_index: Option<i64>,
+    /// Index of the input in the transaction for coin_input records
+    pub input_index: Option<i64>,
+    /// Index of the output in the transaction for output records
+    pub output_index: Option<i64>,
+}
+
+/// Filters that the transfers have to match, empty sets match everything.
+pub struct TransferFilter {
+    /// Hex encoded asset ids
+    pub asset_ids: HashSet<String>,
+    /// Hex encoded addresses and contracts, a transfer matches if either side is one of them
+    pub parties: HashSet<String>,
+    pub include_failed: bool,
+}
+
+impl TransferFilter {
+    fn matches(&self, transfer: &Transfer) -> bool {
+        if !self.include_failed && transfer.tx_status != TX_STATUS_SUCCESS {
+            return false;
+        }
+        if !self.asset_ids.is_empty()
+            && !transfer
+                .asset_id
+                .as_ref()
+                .is_some_and(|asset_id| self.asset_ids.contains(asset_id))
+        {
+            return false;
+        }
+        if !self.parties.is_empty() {
+            let is_party = |party: &Option<String>| {
+                party
+                    .as_ref()
+                    .is_some_and(|party| self.parties.contains(party))
+            };
+            if !is_party(&transfer.from) && !is_party(&transfer.to) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Extract the transfers that match the filter from the response data.
+///
+/// Transfers are ordered by block and transaction, and within a transaction inputs come
+/// first, then receipts, then outputs.
+pub fn extract_transfers(data: QueryResponseDataTyped, filter: &TransferFilter) -> Vec<Transfer> {
+    let mut txs: HashMap<String, usize> = HashMap::new();
+    let mut tx_ordinal = |tx_id: &str| {
+        let next = txs.len();
+        *txs.entry(tx_id.to_owned()).or_insert(next)
+    };
+
+    // (block height, tx ordinal, section, index) to sort by
+    let mut transfers: Vec<((i64, usize, u8, i64), Transfer)> = Vec::new();
+
+    let mut input_indexes = TxIndexes::default();
+    for input in data.inputs {
+        let index = input_indexes.next(&input.tx_id);
+        if input.input_type != INPUT_COIN {
+            continue;
+        }
+        let key = (input.block_height, tx_ordinal(&input.tx_id), 0, index);
+        transfers.push((
+            key,
+            Transfer {
+                kind: "coin_input".into(),
+                from: input.owner,
+                to: None,
+                asset_id: input.asset_id,
+                amount: input.amount,
+                tx_id: input.tx_id,
+                tx_status: input.tx_status,
+                block_height: input.block_height,
+                receipt_index: None,
+                input_index: Some(index),
+                output_index: None,
+            },
+        ));
+    }
+
+    for receipt in data.receipts {
+        let (kind, to) = match receipt.receipt_type {
+            TRANSFER => ("transfer", receipt.to),
+            TRANSFER_OUT => ("transfer_out", receipt.to_address),
+            _ => continue,
+        };
+        let key = (
+            receipt.block_height,
+            tx_ordinal(&receipt.tx_id),
+            1,
+            receipt.receipt_index,
+        );
+        transfers.push((
+            key,
+            Transfer {
+                kind: kind.into(),
+                from: receipt.contract_id,
+                to,
+                asset_id: receipt.asset_id,
+                amount: receipt.amount,
+                tx_id: receipt.tx_id,
+                tx_status: receipt.tx_status,
+                block_height: receipt.block_height,
+                receipt_index: Some(receipt.receipt_index),
+                input_index: None,
+                output_index: None,
+            },
+        ));
+    }
+
+    let mut output_indexes = TxIndexes::default();
+    for output in data.outputs {
+        let index = output_indexes.next(&output.tx_id);
+        let kind = match output.output_type {
+            OUTPUT_COIN => "coin_output",
+            OUTPUT_CHANGE => "change_output",
+            OUTPUT_VARIABLE => "variable_output",
+            _ => continue,
+        };
+        let key = (output.block_height, tx_ordinal(&output.tx_id), 2, index);
+        transfers.push((
+            key,
+            Transfer {
+                kind: kind.into(),
+                from: None,
+                to: output.to,
+                asset_id: output.asset_id,
+                amount: output.amount,
+                tx_id: output.tx_id,
+                tx_status: output.tx_status,
+                block_height: output.block_height,
+                receipt_index: None,
+                input_index: None,
+                output_index: Some(index),
+            },
+        ));
+    }
+
+    transfers.retain(|(_, transfer)| filter.matches(transfer));
+    transfers.sort_by_key(|(key, _)| *key);
+
+    transfers
+        .into_iter()
+        .map(|(_, transfer)| transfer)
+        .collect()
+}
@@ -0,0 +1,1043 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use hyper::client::connect::dns::Name;
+use hyperfuel_client::{ArrowIpc, TransportFormat};
+use hyperfuel_net_types::{ArchiveHeight, Query};
+use rand::Rng;
+use reqwest::{
+    dns::{Addrs, Resolve, Resolving},
+    header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER},
+    ClientBuilder, Method, NoProxy, Proxy, StatusCode,
+};
+use rustls::{
+    client::{
+        ClientSessionMemoryCache, ClientSessionStore, Resumption, ServerName,
+        Tls12ClientSessionValue, Tls13ClientSessionValue,
+    },
+    KeyLog, NamedGroup, OwnedTrustAnchor, RootCertStore,
+};
+use url::Url;
+
+use crate::{
+    auth::{self, TokenProvider},
+    config::{self, ProxyConfig, TlsConfig},
+    fixtures::{FixtureError, Fixtures, RecordedRequest},
+    metrics::{as_millis, RequestMetrics},
+    openmetrics::ClientStats,
+    rate_limit::RateLimiter,
+};
+#[cfg(feature = "fault-injection")]
+use crate::{
+    config::FaultInjectionConfig,
+    faults::{Fault, FaultInjector, InjectedFault},
+};
+
+const USER_AGENT: &str = concat!("hyperfuel-client-node/", env!("CARGO_PKG_VERSION"));
+
+tokio::task_local! {
+    static CONNECT_INFO: Arc<Mutex<ConnectInfo>>;
+}
+
+#[derive(Default)]
+struct ConnectInfo {
+    new_connection: bool,
+    dns_time: Option<Duration>,
+    /// When the host name was resolved, the tcp connect starts right after it
+    dns_end: Option<Instant>,
+    /// When the tls handshake started, which is right after the tcp connection was established
+    tls_start: Option<Instant>,
+    /// When the tls handshake finished
+    tls_end: Option<Instant>,
+}
+
+impl ConnectInfo {
+    /// Update the connect info of the request that is opening the current connection.
+    ///
+    /// Does nothing if the connection is opened in the background, which happens when a
+    /// pooled connection became available first.
+    fn update(f: impl FnOnce(&mut ConnectInfo)) {
+        let _ = CONNECT_INFO.try_with(|info| f(&mut info.lock().unwrap()));
+    }
+
+    /// Time of the tcp connect, only known for tls connections since the start of the
+    /// handshake is the first thing that can be observed after connecting.
+    fn connect_time(&self) -> Option<Duration> {
+        Some(self.tls_start?.saturating_duration_since(self.dns_end?))
+    }
+
+    fn tls_handshake_time(&self) -> Option<Duration> {
+        Some(self.tls_end?.saturating_duration_since(self.tls_start?))
+    }
+}
+
+/// Resolves host names using the system resolver and records the time it took
+/// into the request that is opening the connection.
+struct TimingResolver;
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let info = CONNECT_INFO.try_with(|i| i.clone()).ok();
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+            if let Some(info) = info {
+                let mut info = info.lock().unwrap();
+                info.new_connection = true;
+                info.dns_time = Some(start.elapsed());
+                info.dns_end = Some(Instant::now());
+            }
+            let addrs: Addrs = Box::new(addrs.collect::<Vec<SocketAddr>>().into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Session store that records when a tls handshake starts and when a tls 1.2 handshake
+/// finishes.
+///
+/// rustls looks up the session to resume as the first step of every handshake and stores
+/// the session of a tls 1.2 handshake once the server finished it.
+struct TimingSessionStore(ClientSessionMemoryCache);
+
+impl ClientSessionStore for TimingSessionStore {
+    fn set_kx_hint(&self, server_name: &ServerName, group: NamedGroup) {
+        self.0.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName) -> Option<NamedGroup> {
+        self.0.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: &ServerName, value: Tls12ClientSessionValue) {
+        ConnectInfo::update(|info| info.tls_end = Some(Instant::now()));
+        self.0.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName) -> Option<Tls12ClientSessionValue> {
+        self.0.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName) {
+        self.0.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(&self, server_name: &ServerName, value: Tls13ClientSessionValue) {
+        self.0.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(&self, server_name: &ServerName) -> Option<Tls13ClientSessionValue> {
+        ConnectInfo::update(|info| {
+            info.new_connection = true;
+            info.tls_start = Some(Instant::now());
+        });
+        self.0.take_tls13_ticket(server_name)
+    }
+}
+
+/// Records when the traffic keys of a tls 1.3 handshake are derived, which happens once the
+/// server finished the handshake. The secrets themselves are never looked at.
+struct TimingKeyLog;
+
+impl KeyLog for TimingKeyLog {
+    fn will_log(&self, label: &str) -> bool {
+        label == "CLIENT_TRAFFIC_SECRET_0"
+    }
+
+    fn log(&self, _label: &str, _client_random: &[u8], _secret: &[u8]) {
+        ConnectInfo::update(|info| info.tls_end = Some(Instant::now()));
+    }
+}
+
+/// Error returned when the hyperfuel instance responds with a non success status code.
+#[derive(Debug)]
+pub struct StatusError {
+    pub status: StatusCode,
+    pub body: String,
+    /// Wait suggested by the `Retry-After` header of the response
+    pub retry_after: Option<Duration>,
+}
+
+impl StatusError {
+    /// Whether the instance is throttling the client or temporarily unavailable.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS
+            || self.status == StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "http response status code {}, err body: {}",
+            self.status, self.body
+        )
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// Error returned when requests are still rate limited after the retry budget ran out.
+#[derive(Debug)]
+pub struct RateLimitedError {
+    pub status: StatusCode,
+    /// Wait suggested by the server before sending the next request
+    pub retry_after: Option<Duration>,
+    pub body: String,
+}
+
+impl fmt::Display for RateLimitedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rate limited with status code {}", self.status)?;
+        if let Some(retry_after) = self.retry_after {
+            write!(f, ", retry after {}ms", retry_after.as_millis())?;
+        }
+        write!(f, ", err body: {}", self.body)
+    }
+}
+
+impl std::error::Error for RateLimitedError {}
+
+/// Error returned when every endpoint is behind the block that a request starts from.
+#[derive(Debug)]
+pub struct EndpointsBehindError {
+    pub from_block: u64,
+    /// Highest archive height reported by any endpoint
+    pub archive_height: Option<u64>,
+}
+
+impl fmt::Display for EndpointsBehindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "every endpoint is behind block {}", self.from_block)?;
+        if let Some(height) = self.archive_height {
+            write!(f, ", the highest archive height is {}", height)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EndpointsBehindError {}
+
+/// Classify an error returned by the transport into a short code.
+///
+/// Returns the status code for error responses and the kind of failure for network errors.
+pub fn error_code(err: &anyhow::Error) -> String {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<StatusError>() {
+            return e.status.as_u16().to_string();
+        }
+        if let Some(e) = cause.downcast_ref::<RateLimitedError>() {
+            return e.status.as_u16().to_string();
+        }
+        if cause.downcast_ref::<EndpointsBehindError>().is_some() {
+            return "behind".to_owned();
+        }
+        #[cfg(feature = "fault-injection")]
+        if let Some(InjectedFault(fault)) = cause.downcast_ref::<InjectedFault>() {
+            let code = match fault {
+                Fault::Timeout => "timeout",
+                _ => "network",
+            };
+            return code.to_owned();
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            let code = if e.is_timeout() {
+                "timeout"
+            } else if e.is_connect() {
+                "connect"
+            } else if e.is_body() || e.is_decode() {
+                "body"
+            } else {
+                "network"
+            };
+            return code.to_owned();
+        }
+    }
+
+    "other".to_owned()
+}
+
+/// Whether a request that failed with this error might succeed on another attempt or instance.
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<StatusError>() {
+            return e.status.is_server_error() || e.is_rate_limited();
+        }
+        // the endpoints catch up with the chain over time
+        if cause.downcast_ref::<EndpointsBehindError>().is_some() {
+            return true;
+        }
+        #[cfg(feature = "fault-injection")]
+        if cause.downcast_ref::<InjectedFault>().is_some() {
+            return true;
+        }
+        if cause.downcast_ref::<reqwest::Error>().is_some() {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Whether retrying the request can't fix the error, so operations that retry until they
+/// succeed should give up.
+///
+/// That is the case if the server rejected the request itself, if rate limited requests were
+/// already retried by the transport until the retry budget ran out, or if a replayed request
+/// has no matching fixture.
+pub fn is_fatal(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<StatusError>() {
+            return e.status.is_client_error()
+                && e.status != StatusCode::REQUEST_TIMEOUT
+                && !e.is_rate_limited();
+        }
+        cause.downcast_ref::<RateLimitedError>().is_some()
+            || cause.downcast_ref::<FixtureError>().is_some()
+    })
+}
+
+/// Wait suggested by the server if the request failed because it was rate limited.
+pub fn retry_after(err: &anyhow::Error) -> Option<Duration> {
+    err.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<RateLimitedError>()
+            .and_then(|e| e.retry_after)
+    })
+}
+
+/// Raw response body of a request along with the network metrics of the request.
+pub struct RawResponse {
+    pub bytes: Bytes,
+    pub metrics: RequestMetrics,
+    /// Index of the endpoint that served the request
+    pub endpoint: usize,
+}
+
+/// A hyperfuel instance the client can send requests to.
+struct Endpoint {
+    url: Url,
+    bearer_token: Option<String>,
+    priority: i64,
+    weight: u32,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+    archive_height: Option<u64>,
+}
+
+impl Endpoint {
+    fn record_success(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures = 0;
+        health.unhealthy_until = None;
+    }
+
+    /// Mark the endpoint as unhealthy, for `wait` if given or for an exponential backoff.
+    fn record_failure(&self, wait: Option<Duration>) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures += 1;
+        let backoff = ENDPOINT_BACKOFF_BASE * 2u32.pow(health.consecutive_failures.min(8) - 1);
+        let wait = wait.unwrap_or(backoff.min(ENDPOINT_BACKOFF_MAX));
+        health.unhealthy_until = Some(Instant::now() + wait);
+    }
+
+    fn archive_height(&self) -> Option<u64> {
+        self.health.lock().unwrap().archive_height
+    }
+
+    fn unhealthy_until(&self) -> Option<Instant> {
+        self.health
+            .lock()
+            .unwrap()
+            .unhealthy_until
+            .filter(|&until| until > Instant::now())
+    }
+
+    /// Whether the endpoint is known to not have reached the block that a request starts from.
+    fn is_behind(&self, from_block: u64) -> bool {
+        self.archive_height()
+            .is_some_and(|height| height.saturating_add(1) < from_block)
+    }
+}
+
+const ENDPOINT_BACKOFF_BASE: Duration = Duration::from_millis(500);
+const ENDPOINT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Rate limited requests are not retried if the server asks to wait longer than this.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Http layer of the client. Sends requests to the hyperfuel instances and measures them.
+///
+/// Requests that fail with a retryable error are sent to the next best endpoint until
+/// every endpoint was tried once.
+pub struct Transport {
+    http_client: reqwest::Client,
+    tokens: TokenProvider,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    rate_limit_retries: u32,
+    fixtures: Option<Fixtures>,
+    endpoints: Vec<Endpoint>,
+    max_lag_blocks: Option<u64>,
+    stats: Option<Arc<ClientStats>>,
+    #[cfg(feature = "fault-injection")]
+    timeout: Duration,
+    #[cfg(feature = "fault-injection")]
+    faults: Option<FaultInjector>,
+}
+
+impl Transport {
+    /// Build the transport, failed attempts that are retried are counted in `stats`.
+    pub fn new(node_cfg: &config::Config, stats: Option<Arc<ClientStats>>) -> Result<Self> {
+        let cfg = node_cfg.try_convert().context("parse config")?;
+        let timeout = Duration::from_millis(cfg.http_req_timeout_millis.get());
+
+        let user_agent = match &node_cfg.user_agent_suffix {
+            Some(suffix) => format!("{} {}", USER_AGENT, suffix),
+            None => USER_AGENT.to_owned(),
+        };
+
+        let mut builder = reqwest::Client::builder()
+            .http1_only()
+            .timeout(timeout)
+            .tcp_keepalive(Duration::from_secs(7200))
+            .connect_timeout(timeout)
+            .dns_resolver(Arc::new(TimingResolver))
+            .user_agent(user_agent);
+
+        if let Some(headers) = &node_cfg.http_headers {
+            builder = builder.default_headers(header_map(headers).context("parse http headers")?);
+        }
+        if let Some(proxy) = &node_cfg.proxy {
+            builder = with_proxy(builder, proxy).context("configure proxy")?;
+        }
+        builder = builder
+            .use_preconfigured_tls(tls_config(node_cfg.tls.as_ref()).context("configure tls")?);
+
+        let http_client = builder.build().context("build http client")?;
+
+        let tokens = TokenProvider::new(node_cfg).context("configure bearer token")?;
+
+        let mut endpoints = vec![Endpoint {
+            url: cfg.url.clone(),
+            bearer_token: None,
+            priority: 0,
+            weight: 1,
+            health: Default::default(),
+        }];
+        for endpoint in node_cfg.endpoints.iter().flatten() {
+            let url = Url::parse(&endpoint.url)
+                .with_context(|| format!("parse endpoint url {}", endpoint.url))?;
+            let weight = endpoint.weight.unwrap_or(1);
+            if weight <= 0 {
+                return Err(anyhow!("weight of endpoint {} has to be positive", url));
+            }
+            endpoints.push(Endpoint {
+                url,
+                bearer_token: endpoint.bearer_token.clone(),
+                priority: endpoint.priority.unwrap_or(0),
+                weight: weight.try_into().unwrap_or(u32::MAX),
+                health: Default::default(),
+            });
+        }
+
+        let max_lag_blocks = node_cfg
+            .endpoint_max_lag_blocks
+            .map(|lag| {
+                lag.try_into()
+                    .context("endpoint_max_lag_blocks is negative")
+            })
+            .transpose()?;
+
+        let rate_limiter = RateLimiter::from_config(node_cfg).context("configure rate limit")?;
+
+        let rate_limit_retries = node_cfg
+            .rate_limit_retries
+            .unwrap_or(3)
+            .try_into()
+            .context("rate_limit_retries is negative")?;
+
+        for token in endpoints.iter().filter_map(|e| e.bearer_token.as_deref()) {
+            auth::register_secret(token);
+        }
+
+        let fixtures = Fixtures::from_config(node_cfg).context("open fixtures")?;
+
+        Ok(Self {
+            http_client,
+            tokens,
+            rate_limiter,
+            rate_limit_retries,
+            fixtures,
+            endpoints,
+            max_lag_blocks,
+            stats,
+            #[cfg(feature = "fault-injection")]
+            timeout,
+            #[cfg(feature = "fault-injection")]
+            faults: None,
+        })
+    }
+
+    /// Make requests fail on purpose as configured in `faults`.
+    #[cfg(feature = "fault-injection")]
+    pub fn inject_faults(&mut self, faults: &FaultInjectionConfig) -> Result<()> {
+        self.faults = Some(FaultInjector::new(faults, self.timeout)?);
+        Ok(())
+    }
+
+    /// Source of the bearer token for endpoints that don't have their own
+    pub fn tokens(&self) -> &TokenProvider {
+        &self.tokens
+    }
+
+    /// Record the archive height that an endpoint reported in a response.
+    pub fn set_archive_height(&self, endpoint: usize, height: u64) {
+        self.endpoints[endpoint]
+            .health
+            .lock()
+            .unwrap()
+            .archive_height = Some(height);
+    }
+
+    /// Get the height of the source hyperfuel instance
+    pub async fn get_height(&self) -> Result<(u64, RequestMetrics)> {
+        let recorded = RecordedRequest {
+            method: Method::GET.to_string(),
+            path: "height".to_owned(),
+            body: None,
+        };
+        let res = self
+            .send_recorded(recorded, 0, |url| self.height_request(url))
+            .await?;
+
+        let height = parse_height(&res.bytes)?;
+        self.set_archive_height(res.endpoint, height);
+
+        Ok((height, res.metrics))
+    }
+
+    fn height_request(&self, url: &Url) -> Result<reqwest::RequestBuilder> {
+        let mut url = url.clone();
+        let mut segments = url.path_segments_mut().ok().context("get path segments")?;
+        segments.push("height");
+        std::mem::drop(segments);
+        Ok(self.http_client.request(Method::GET, url))
+    }
+
+    /// Ask the endpoints that weren't tried yet for their current height, since the heights
+    /// they reported last might be outdated.
+    async fn refresh_heights(&self, tried: &[usize]) {
+        let untried = (0..self.endpoints.len()).filter(|idx| !tried.contains(idx));
+        futures::future::join_all(untried.map(|idx| async move {
+            let endpoint = &self.endpoints[idx];
+            let res = self
+                .send_authorized(endpoint, &|url: &Url| self.height_request(url))
+                .await
+                .and_then(|res| parse_height(&res.bytes));
+            match res {
+                Ok(height) => self.set_archive_height(idx, height),
+                Err(e) => log::debug!(
+                    "failed to refresh height of {}: {}",
+                    endpoint.url,
+                    auth::redact_error(&e)
+                ),
+            }
+        }))
+        .await;
+    }
+
+    /// Error for a request that every endpoint is too far behind to serve.
+    fn behind_error(&self, from_block: u64) -> EndpointsBehindError {
+        EndpointsBehindError {
+            from_block,
+            archive_height: self
+                .endpoints
+                .iter()
+                .filter_map(|e| e.archive_height())
+                .max(),
+        }
+    }
+
+    /// Send a query to the source hyperfuel instance and return the raw response body.
+    ///
+    /// The traceparent is sent along so the server side spans join the trace of the caller.
+    ///
+    /// Endpoints that are known to be behind the `from_block` of the query are not used,
+    /// so a paginated stream never goes back to an instance that hasn't reached its cursor.
+    pub async fn query(&self, query: &Query, traceparent: &str) -> Result<RawResponse> {
+        let recorded = RecordedRequest {
+            method: Method::POST.to_string(),
+            path: format!("query/{}", ArrowIpc::path()),
+            body: Some(serde_json::to_value(query).context("serialize query")?),
+        };
+
+        log::trace!("sending req to hyperfuel");
+        self.send_recorded(recorded, query.from_block, |url| {
+            let mut url = url.clone();
+            let mut segments = url.path_segments_mut().ok().context("get path segments")?;
+            segments.push("query");
+            segments.push(ArrowIpc::path());
+            std::mem::drop(segments);
+            Ok(self
+                .http_client
+                .request(Method::POST, url)
+                .header("traceparent", traceparent)
+                .json(query))
+        })
+        .await
+    }
+
+    /// Send the request, or serve it from the fixtures in replay mode.
+    ///
+    /// The outcome of the request is saved to the fixtures in record mode.
+    async fn send_recorded(
+        &self,
+        recorded: RecordedRequest,
+        from_block: u64,
+        make_req: impl Fn(&Url) -> Result<reqwest::RequestBuilder>,
+    ) -> Result<RawResponse> {
+        let Some(fixtures) = &self.fixtures else {
+            return self.send_with_failover(from_block, make_req).await;
+        };
+
+        if fixtures.is_replay() {
+            let bytes = fixtures.replay(&recorded)?;
+            let metrics = RequestMetrics {
+                response_bytes: bytes.len() as i64,
+                ..Default::default()
+            };
+            return Ok(RawResponse {
+                bytes,
+                metrics,
+                endpoint: 0,
+            });
+        }
+
+        let res = self.send_with_failover(from_block, make_req).await;
+        fixtures
+            .record(&recorded, res.as_ref().map(|res| &res.bytes))
+            .context("record fixture")?;
+
+        res
+    }
+
+    /// Send the request to the best endpoint, failing over to the others on retryable errors.
+    ///
+    /// If every endpoint rate limited the request, waits for the `Retry-After` suggested by the
+    /// servers (or an exponential backoff) plus jitter and starts over, until the rate limit
+    /// retries run out.
+    async fn send_with_failover(
+        &self,
+        from_block: u64,
+        make_req: impl Fn(&Url) -> Result<reqwest::RequestBuilder>,
+    ) -> Result<RawResponse> {
+        let mut tried = Vec::with_capacity(self.endpoints.len());
+        let mut rate_limited_rounds = 0;
+
+        loop {
+            let idx = match self.select_endpoint(from_block, &tried) {
+                Some(idx) => idx,
+                None => {
+                    self.refresh_heights(&tried).await;
+                    self.select_endpoint(from_block, &tried)
+                        .ok_or_else(|| self.behind_error(from_block))?
+                }
+            };
+            let endpoint = &self.endpoints[idx];
+
+            let e = match self.send_authorized(endpoint, &make_req).await {
+                Ok(mut res) => {
+                    endpoint.record_success();
+                    res.endpoint = idx;
+                    res.metrics.endpoint = endpoint.url.to_string();
+                    return Ok(res);
+                }
+                Err(e) if is_retryable(&e) => e,
+                Err(e) => return Err(e),
+            };
+
+            let rate_limited = e
+                .downcast_ref::<StatusError>()
+                .filter(|e| e.is_rate_limited());
+            endpoint.record_failure(rate_limited.and_then(|e| e.retry_after));
+            tried.push(idx);
+
+            if tried.len() < self.endpoints.len() {
+                self.record_retry(&e);
+                log::warn!(
+                    "request to {} failed, failing over to next endpoint: {}",
+                    endpoint.url,
+                    auth::redact_error(&e)
+                );
+                continue;
+            }
+
+            let Some(rate_limited) = rate_limited else {
+                return Err(e);
+            };
+
+            let wait = rate_limited
+                .retry_after
+                .unwrap_or_else(|| ENDPOINT_BACKOFF_BASE * 2u32.pow(rate_limited_rounds.min(6)));
+            if rate_limited_rounds >= self.rate_limit_retries || wait > MAX_RATE_LIMIT_WAIT {
+                return Err(RateLimitedError {
+                    status: rate_limited.status,
+                    retry_after: rate_limited.retry_after,
+                    body: rate_limited.body.clone(),
+                }
+                .into());
+            }
+
+            self.record_retry(&e);
+            let jitter = wait.mul_f64(rand::thread_rng().gen_range(0.0..0.2));
+            log::warn!(
+                "rate limited by {} with status {}, retrying in {:?}",
+                endpoint.url,
+                rate_limited.status,
+                wait + jitter
+            );
+            tokio::time::sleep(wait + jitter).await;
+
+            rate_limited_rounds += 1;
+            tried.clear();
+        }
+    }
+
+    /// Send the request to the endpoint, refreshing the bearer token and retrying once
+    /// if the endpoint rejects the token.
+    async fn send_authorized(
+        &self,
+        endpoint: &Endpoint,
+        make_req: &impl Fn(&Url) -> Result<reqwest::RequestBuilder>,
+    ) -> Result<RawResponse> {
+        let res = self.send(endpoint, make_req(&endpoint.url)?).await;
+
+        let unauthorized = match &res {
+            Err(e) => e
+                .downcast_ref::<StatusError>()
+                .is_some_and(|e| e.status == StatusCode::UNAUTHORIZED),
+            Ok(_) => false,
+        };
+        if !unauthorized || endpoint.bearer_token.is_some() || !self.tokens.is_refreshable() {
+            return res;
+        }
+        if let Err(e) = &res {
+            self.record_retry(e);
+        }
+
+        log::warn!(
+            "request to {} was unauthorized, refreshing bearer token",
+            endpoint.url
+        );
+        self.tokens
+            .refresh()
+            .await
+            .context("refresh bearer token")?;
+
+        self.send(endpoint, make_req(&endpoint.url)?).await
+    }
+
+    /// Count a failed attempt that is going to be retried in the statistics of the client.
+    fn record_retry(&self, e: &anyhow::Error) {
+        if let Some(stats) = &self.stats {
+            stats.record_error(&error_code(e));
+            stats.record_retry();
+        }
+    }
+
+    /// Pick the endpoint to send the next request to.
+    ///
+    /// Endpoints that are behind `from_block` are skipped, `None` is returned if every
+    /// endpoint that wasn't tried yet is behind. Among the rest, healthy endpoints of the
+    /// lowest priority are picked randomly by weight. If none are healthy, the endpoint that
+    /// becomes healthy first is used.
+    fn select_endpoint(&self, from_block: u64, tried: &[usize]) -> Option<usize> {
+        let reachable: Vec<usize> = (0..self.endpoints.len())
+            .filter(|idx| !tried.contains(idx))
+            .filter(|&idx| !self.endpoints[idx].is_behind(from_block))
+            .collect();
+        if reachable.is_empty() {
+            return None;
+        }
+
+        let best_height = self
+            .endpoints
+            .iter()
+            .filter_map(|e| e.archive_height())
+            .max();
+        let is_lagging = |e: &Endpoint| match (self.max_lag_blocks, best_height, e.archive_height())
+        {
+            (Some(max_lag), Some(best), Some(height)) => best.saturating_sub(height) > max_lag,
+            _ => false,
+        };
+
+        let healthy: Vec<usize> = reachable
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                let e = &self.endpoints[idx];
+                e.unhealthy_until().is_none() && !is_lagging(e)
+            })
+            .collect();
+        if healthy.is_empty() {
+            return reachable
+                .into_iter()
+                .min_by_key(|&idx| self.endpoints[idx].unhealthy_until());
+        }
+
+        let priority = healthy
+            .iter()
+            .map(|&idx| self.endpoints[idx].priority)
+            .min()?;
+        let candidates: Vec<usize> = healthy
+            .into_iter()
+            .filter(|&idx| self.endpoints[idx].priority == priority)
+            .collect();
+
+        let total_weight: u64 = candidates
+            .iter()
+            .map(|&idx| u64::from(self.endpoints[idx].weight))
+            .sum();
+        let mut pick = rand::thread_rng().gen_range(0..total_weight);
+        for &idx in candidates.iter() {
+            let weight = u64::from(self.endpoints[idx].weight);
+            if pick < weight {
+                return Some(idx);
+            }
+            pick -= weight;
+        }
+
+        candidates.last().copied()
+    }
+
+    async fn send(
+        &self,
+        endpoint: &Endpoint,
+        mut req: reqwest::RequestBuilder,
+    ) -> Result<RawResponse> {
+        let bearer_token = match &endpoint.bearer_token {
+            Some(token) => Some(token.clone()),
+            None => self.tokens.token().await.context("get bearer token")?,
+        };
+        if let Some(bearer_token) = bearer_token {
+            req = req.bearer_auth(bearer_token);
+        }
+
+        let start = Instant::now();
+        let _permit = match &self.rate_limiter {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        };
+        let rate_limit_wait = start.elapsed();
+
+        #[cfg(feature = "fault-injection")]
+        let fault = self
+            .faults
+            .as_ref()
+            .and_then(|faults| Some((faults, faults.next()?)));
+        #[cfg(feature = "fault-injection")]
+        if let Some((faults, fault)) = fault {
+            log::debug!("injecting fault {:?} into request", fault);
+            faults
+                .fail_request(fault)
+                .await
+                .context("execute http req")?;
+        }
+
+        let info = Arc::new(Mutex::new(ConnectInfo::default()));
+
+        let start = Instant::now();
+        let res = CONNECT_INFO
+            .scope(info.clone(), req.send())
+            .await
+            .context("execute http req")?;
+        #[cfg(feature = "fault-injection")]
+        if let Some((faults, fault)) = fault {
+            faults.delay_response(fault).await;
+        }
+        let time_to_first_byte = start.elapsed();
+
+        let status = res.status();
+        if !status.is_success() {
+            let retry_after = res
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_retry_after);
+            let body = res.text().await.context("read text to see error")?;
+            let body = auth::redact(&body);
+
+            return Err(StatusError {
+                status,
+                body,
+                retry_after,
+            }
+            .into());
+        }
+
+        let start = Instant::now();
+        let bytes = res.bytes().await.context("read response body bytes")?;
+        #[cfg(feature = "fault-injection")]
+        let bytes = match fault {
+            Some((faults, fault)) => faults.damage_body(fault, bytes),
+            None => bytes,
+        };
+        let transfer_time = start.elapsed();
+
+        let info = info.lock().unwrap();
+        let metrics = RequestMetrics {
+            rate_limit_wait_ms: as_millis(rate_limit_wait),
+            dns_time_ms: info.dns_time.map(as_millis),
+            connect_time_ms: info.connect_time().map(as_millis),
+            tls_handshake_time_ms: info.tls_handshake_time().map(as_millis),
+            new_connection: info.new_connection,
+            time_to_first_byte_ms: as_millis(time_to_first_byte),
+            transfer_time_ms: as_millis(transfer_time),
+            response_bytes: bytes.len() as i64,
+            ..Default::default()
+        };
+
+        Ok(RawResponse {
+            bytes,
+            metrics,
+            endpoint: 0,
+        })
+    }
+}
+
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers.iter() {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid header name {}", name))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("invalid value for header {}", name))?;
+        map.insert(name, value);
+    }
+
+    Ok(map)
+}
+
+fn with_proxy(mut builder: ClientBuilder, cfg: &ProxyConfig) -> Result<ClientBuilder> {
+    let no_proxy = cfg
+        .no_proxy
+        .as_ref()
+        .and_then(|hosts| NoProxy::from_string(&hosts.join(",")));
+
+    let proxies = [
+        cfg.http.as_deref().map(|url| (url, Proxy::http(url))),
+        cfg.https.as_deref().map(|url| (url, Proxy::https(url))),
+        cfg.all.as_deref().map(|url| (url, Proxy::all(url))),
+    ];
+
+    for (url, proxy) in proxies.into_iter().flatten() {
+        let proxy = proxy.with_context(|| format!("invalid proxy url {}", url))?;
+        builder = builder.proxy(proxy.no_proxy(no_proxy.clone()));
+    }
+
+    Ok(builder)
+}
+
+/// Build the rustls config of the client, which records the timings of tls handshakes.
+fn tls_config(cfg: Option<&TlsConfig>) -> Result<rustls::ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+
+    let cfg = cfg.cloned().unwrap_or_default();
+    if let Some(path) = &cfg.ca_bundle_path {
+        let pem = std::fs::read(path).with_context(|| format!("read ca bundle {}", path))?;
+        let certs = rustls_pemfile::certs(&mut pem.as_slice()).context("parse ca bundle")?;
+        for cert in certs {
+            roots
+                .add(&rustls::Certificate(cert))
+                .context("parse ca bundle")?;
+        }
+    }
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+
+    let mut config = match (&cfg.client_cert_path, &cfg.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let pem = std::fs::read(cert_path)
+                .with_context(|| format!("read client certificate {}", cert_path))?;
+            let certs = rustls_pemfile::certs(&mut pem.as_slice())
+                .context("parse client certificate")?
+                .into_iter()
+                .map(rustls::Certificate)
+                .collect();
+            let pem =
+                std::fs::read(key_path).with_context(|| format!("read client key {}", key_path))?;
+            let key = client_key(&pem).context("parse client key")?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .context("parse client certificate")?
+        }
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(anyhow!(
+                "client_cert_path and client_key_path have to be set together"
+            ))
+        }
+    };
+
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    config.resumption = Resumption::store(Arc::new(TimingSessionStore(
+        ClientSessionMemoryCache::new(256),
+    )));
+    config.key_log = Arc::new(TimingKeyLog);
+
+    Ok(config)
+}
+
+fn client_key(pem: &[u8]) -> Result<rustls::PrivateKey> {
+    let mut reader = pem;
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(rustls::PrivateKey(key)),
+            _ => (),
+        }
+    }
+
+    Err(anyhow!("no private key found"))
+}
+
+fn parse_height(bytes: &[u8]) -> Result<u64> {
+    let height: ArchiveHeight = serde_json::from_slice(bytes).context("read response body json")?;
+    Ok(height.height.unwrap_or(0))
+}
+
+/// Parse a `Retry-After` header value, which is either a number of seconds or an http date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
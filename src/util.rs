@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+/// Decode a hex string with or without the 0x prefix, an odd number of digits is padded with
+/// a leading zero.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let hex = if hex.len() % 2 == 1 {
+        format!("0{}", hex)
+    } else {
+        hex.to_owned()
+    };
+    let mut out = vec![0; hex.len() / 2];
+    faster_hex::hex_decode(hex.as_bytes(), &mut out).context("decode hex")?;
+    Ok(out)
+}
+
+/// Derives the index of inputs or outputs within their transaction from their position in a
+/// response.
+///
+/// The response has to contain every input or output of the returned transactions, which is
+/// the case for the preset queries that select whole transactions.
+#[derive(Default)]
+pub struct TxIndexes(HashMap<String, i64>);
+
+impl TxIndexes {
+    /// Index of the next input or output of the transaction.
+    pub fn next(&mut self, tx_id: &str) -> i64 {
+        let count = self.0.entry(tx_id.to_owned()).or_default();
+        let index = *count;
+        *count += 1;
+        index
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use napi::bindgen_prelude::BigInt;
+
+use crate::{
+    response::QueryResponseDataTyped,
+    types::{Input, Output},
+    util::{decode_hex, TxIndexes},
+};
+
+const INPUT_COIN: u8 = 0;
+const OUTPUT_COIN: u8 = 0;
+const OUTPUT_CHANGE: u8 = 2;
+const OUTPUT_VARIABLE: u8 = 3;
+
+/// The output that a coin input spends.
+#[napi(object)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UtxoId {
+    /// Transaction that created the output
+    pub tx_id: String,
+    /// Index of the output in the transaction, not set if the utxo id doesn't contain it
+    pub output_index: Option<i64>,
+}
+
+/// Decode a utxo id into the transaction that created the output and the index of the output.
+///
+/// Accepts the 34 byte form (transaction id followed by a big endian u16 index), the 40 byte
+/// canonical form that pads the index to 8 bytes, and 32 byte ids that only contain the
+/// transaction id.
+pub fn decode_utxo_id(utxo_id: &str) -> Result<UtxoId> {
+    let bytes = decode_hex(utxo_id)?;
+    let output_index = match bytes.len() {
+        32 => None,
+        34 => Some(u16::from_be_bytes([bytes[32], bytes[33]]) as i64),
+        40 => {
+            let index = u64::from_be_bytes(bytes[32..40].try_into().unwrap());
+            Some(i64::try_from(index).map_err(|_| anyhow!("output index is too large"))?)
+        }
+        len => return Err(anyhow!("utxo id has unexpected length of {} bytes", len)),
+    };
+
+    Ok(UtxoId {
+        tx_id: format!("0x{}", faster_hex::hex_string(&bytes[..32])),
+        output_index,
+    })
+}
+
+/// A coin from its creation to the point it was spent.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct UtxoLifecycle {
+    /// Transaction that created the coin
+    pub tx_id: String,
+    /// Index of the output that created the coin
+    pub output_index: Option<i64>,
+    /// Owner of the coin
+    pub owner: Option<String>,
+    pub asset_id: Option<String>,
+    pub amount: Option<BigInt>,
+    /// Block the coin was created in, not set if it was created before the block range
+    pub created_block_height: Option<i64>,
+    /// Transaction that spent the coin, not set if it is unspent at the end of the block range
+    pub spent_tx_id: Option<String>,
+    /// Block the coin was spent in
+    pub spent_block_height: Option<i64>,
+}
+
+/// Pairs the coin outputs with the coin inputs that spend them across the pages of a query.
+///
+/// Inputs whose utxo id only contains the transaction id are paired with the output of that
+/// transaction that has the same owner, asset and amount.
+pub struct UtxoTracker {
+    owners: HashSet<String>,
+    utxos: HashSet<UtxoId>,
+    /// Coins by creating transaction and output index, in the order they were seen
+    coins: BTreeMap<(usize, i64), UtxoLifecycle>,
+    tx_ordinals: HashMap<String, usize>,
+}
+
+impl UtxoTracker {
+    /// Track the coins of the given owners or the given utxos, empty sets match every coin.
+    pub fn new(owners: HashSet<String>, utxos: HashSet<UtxoId>) -> Self {
+        Self {
+            owners,
+            utxos,
+            coins: BTreeMap::new(),
+            tx_ordinals: Default::default(),
+        }
+    }
+
+    /// Add the inputs and outputs of a page, pages have to be added in block order.
+    pub fn add(&mut self, data: QueryResponseDataTyped) -> Result<()> {
+        let mut output_indexes = TxIndexes::default();
+        for output in data.outputs {
+            let index = output_indexes.next(&output.tx_id);
+            if matches!(
+                output.output_type,
+                OUTPUT_COIN | OUTPUT_CHANGE | OUTPUT_VARIABLE
+            ) {
+                self.created(output, index);
+            }
+        }
+
+        for input in data.inputs {
+            if input.input_type == INPUT_COIN {
+                self.spent(input)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Coins ordered by the block they were created in, coins that were created before the
+    /// block range come first.
+    pub fn finish(self) -> Vec<UtxoLifecycle> {
+        let mut coins: Vec<UtxoLifecycle> = self.coins.into_values().collect();
+        coins.sort_by_key(|coin| coin.created_block_height);
+        coins
+    }
+
+    fn is_tracked(&self, owner: &Option<String>, utxo: &UtxoId) -> bool {
+        let owner_matches = self.owners.is_empty()
+            || owner
+                .as_ref()
+                .is_some_and(|owner| self.owners.contains(owner));
+        let utxo_matches = self.utxos.is_empty()
+            || self.utxos.contains(utxo)
+            || self.utxos.contains(&UtxoId {
+                output_index: None,
+                ..utxo.clone()
+            });
+
+        owner_matches && utxo_matches
+    }
+
+    fn ordinal(&mut self, tx_id: &str) -> usize {
+        let next = self.tx_ordinals.len();
+        *self.tx_ordinals.entry(tx_id.to_owned()).or_insert(next)
+    }
+
+    fn created(&mut self, output: Output, index: i64) {
+        let utxo = UtxoId {
+            tx_id: output.tx_id.clone(),
+            output_index: Some(index),
+        };
+        if !self.is_tracked(&output.to, &utxo) {
+            return;
+        }
+        let key = (self.ordinal(&output.tx_id), index);
+        self.coins.insert(
+            key,
+            UtxoLifecycle {
+                tx_id: output.tx_id,
+                output_index: Some(index),
+                owner: output.to,
+                asset_id: output.asset_id,
+                amount: output.amount,
+                created_block_height: Some(output.block_height),
+                spent_tx_id: None,
+                spent_block_height: None,
+            },
+        );
+    }
+
+    fn spent(&mut self, input: Input) -> Result<()> {
+        let Some(utxo_id) = &input.utxo_id else {
+            return Ok(());
+        };
+        let mut utxo = decode_utxo_id(utxo_id)?;
+        let ordinal = self.ordinal(&utxo.tx_id);
+
+        if utxo.output_index.is_none() {
+            // pick the first unspent output of the transaction that holds the same coin
+            utxo.output_index = self
+                .coins
+                .range((ordinal, 0)..)
+                .take_while(|((tx, _), _)| *tx == ordinal)
+                .find(|(_, coin)| {
+                    coin.spent_tx_id.is_none()
+                        && coin.owner == input.owner
+                        && coin.asset_id == input.asset_id
+                        && same_amount(&coin.amount, &input.amount)
+                })
+                .map(|((_, index), _)| *index);
+        }
+
+        let key = match utxo.output_index {
+            Some(index) => (ordinal, index),
+            // coins created before the block range without a known index are keyed below
+            // every created coin of the transaction
+            None => (ordinal, -(self.coins.len() as i64) - 1),
+        };
+        if !self.coins.contains_key(&key) {
+            if !self.is_tracked(&input.owner, &utxo) {
+                return Ok(());
+            }
+            self.coins.insert(
+                key,
+                UtxoLifecycle {
+                    tx_id: utxo.tx_id,
+                    output_index: utxo.output_index,
+                    owner: input.owner.clone(),
+                    asset_id: input.asset_id.clone(),
+                    amount: input.amount.clone(),
+                    created_block_height: None,
+                    spent_tx_id: None,
+                    spent_block_height: None,
+                },
+            );
+        }
+        let coin = self.coins.get_mut(&key).unwrap();
+        coin.spent_tx_id = Some(input.tx_id);
+        coin.spent_block_height = Some(input.block_height);
+
+        Ok(())
+    }
+}
+
+fn same_amount(a: &Option<BigInt>, b: &Option<BigInt>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.get_u64() == b.get_u64(),
+        (None, None) => true,
+        _ => false,
+    }
+}